@@ -162,10 +162,8 @@ where
     Self: 'static,
 {
     pub fn new(concurrency: u64) -> Self {
-        let temp: Vec<HashMap<String, Process>> = (0..concurrency)
-            .into_iter()
-            .map(|_| HashMap::new())
-            .collect();
+        let temp: Vec<HashMap<String, Process>> =
+            (0..concurrency).map(|_| HashMap::new()).collect();
         let tables = [
             ("0".into(), Process { id: "0".into() }),
             ("1".into(), Process { id: "1".into() }),
@@ -219,6 +217,7 @@ where
     }
 }
 
+#[allow(dead_code)]
 fn worker_thread_concurrent(registry: Arc<ConcurrentRegistry>, i: u64) {
     let key = i % 16;
     if key != 0 {
@@ -229,6 +228,7 @@ fn worker_thread_concurrent(registry: Arc<ConcurrentRegistry>, i: u64) {
     }
 }
 
+#[allow(dead_code)]
 fn run_concurrent_registry(concurrency: u64) {
     let registry = Arc::new(ConcurrentRegistry::new(concurrency));
 
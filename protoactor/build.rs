@@ -1,6 +1,30 @@
+use std::path::PathBuf;
+
 fn main() -> std::io::Result<()> {
     let mut prost_build = prost_build::Config::new();
+    prost_build.type_attribute("actor.PID", "#[derive(Eq, Hash)]");
     prost_build.compile_protos(&["src/protos.proto"], &["src"])?;
 
+    if std::env::var_os("CARGO_FEATURE_REMOTE").is_none() {
+        return Ok(());
+    }
+    // actor package is generated above, remote types refer to it, and the empty actor.rs
+    // emitted for the extern package must not overwrite it.
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("remote");
+    std::fs::create_dir_all(&out_dir)?;
+    let mut prost_build = prost_build::Config::new();
+    prost_build.extern_path(".actor", "crate::message");
+    prost_build.out_dir(out_dir);
+    prost_build.compile_protos(&["src/remote.proto"], &["src"])?;
+
+    if std::env::var_os("CARGO_FEATURE_CLUSTER").is_none() {
+        return Ok(());
+    }
+    let out_dir = PathBuf::from(std::env::var("OUT_DIR").unwrap()).join("cluster");
+    std::fs::create_dir_all(&out_dir)?;
+    let mut prost_build = prost_build::Config::new();
+    prost_build.out_dir(out_dir);
+    prost_build.compile_protos(&["src/cluster.proto"], &["src"])?;
+
     Ok(())
 }
@@ -1,7 +1,30 @@
+mod props;
+
+pub use props::*;
+
+use crate::context::ActorContext;
+use crate::diagnostics::Diagnostics;
+use async_trait::async_trait;
+use std::any::Any;
+
 /// Trait that marks struct as an actor
+///
+/// Every message, including lifecycle messages such as [Started](crate::message::Started), is
+/// delivered through [Actor::receive]. Mailbox processes the next message only after the
+/// returned future completes, use
+/// [ActorContext::reenter_after](crate::context::ActorContext::reenter_after) to await slow
+/// work without blocking the mailbox.
+#[async_trait]
 pub trait Actor
 where
-    Self: Send + Unpin + 'static,
+    Self: Any + Send + Unpin + 'static,
 {
-    type Context;
+    async fn receive(&mut self, ctx: &mut ActorContext);
+
+    /// Opts in to reporting actor state through
+    /// [ActorSystem::diagnostics](crate::system::ActorSystem::diagnostics), actors implementing
+    /// [Diagnostics] return `Some(self)`.
+    fn diagnostics(&self) -> Option<&dyn Diagnostics> {
+        None
+    }
 }
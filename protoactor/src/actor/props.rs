@@ -0,0 +1,164 @@
+use crate::actor::Actor;
+use crate::context::{ActorContext, ContextDecorator};
+use crate::mailbox;
+use crate::message::{Pid, SystemMessage};
+use crate::middleware::{ReceiverMiddleware, SenderMiddleware, SpawnMiddleware};
+use crate::process::{ActorProcess, Process, SpawnError};
+use crate::system::ActorSystem;
+use std::any::type_name;
+use std::sync::Arc;
+
+pub type Producer = Arc<dyn Fn() -> Box<dyn Actor> + Send + Sync>;
+
+/// Replaces the last stage of the spawn pipeline, used by processes such as routers that
+/// wrap the actor process.
+pub(crate) type Spawner = Arc<
+    dyn Fn(&Props, &Arc<ActorSystem>, String, Option<Pid>) -> Result<Pid, SpawnError> + Send + Sync,
+>;
+
+/// Configuration used to spawn an actor.
+#[derive(Clone)]
+pub struct Props {
+    producer: Producer,
+    actor_type: &'static str,
+    pub(crate) receiver_middleware: Vec<Arc<dyn ReceiverMiddleware>>,
+    pub(crate) sender_middleware: Vec<Arc<dyn SenderMiddleware>>,
+    pub(crate) spawn_middleware: Vec<Arc<dyn SpawnMiddleware>>,
+    pub(crate) context_decorators: Vec<Arc<dyn ContextDecorator>>,
+    spawner: Option<Spawner>,
+}
+
+impl Props {
+    /// Creates props that spawn actors created by `producer`.
+    pub fn from_producer<A, F>(producer: F) -> Self
+    where
+        A: Actor,
+        F: Fn() -> A + Send + Sync + 'static,
+    {
+        Self {
+            producer: Arc::new(move || Box::new(producer())),
+            actor_type: type_name::<A>(),
+            receiver_middleware: Vec::new(),
+            sender_middleware: Vec::new(),
+            spawn_middleware: Vec::new(),
+            context_decorators: Vec::new(),
+            spawner: None,
+        }
+    }
+
+    /// Adds middleware that wraps every message received by the actor.
+    pub fn with_receiver_middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: ReceiverMiddleware,
+    {
+        self.receiver_middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Adds middleware that wraps every message sent by the actor.
+    pub fn with_sender_middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: SenderMiddleware,
+    {
+        self.sender_middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Adds middleware that wraps spawning of the actor's children.
+    pub fn with_spawn_middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: SpawnMiddleware,
+    {
+        self.spawn_middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Adds decorator that wraps operations of the actor's context.
+    pub fn with_context_decorator<D>(mut self, decorator: D) -> Self
+    where
+        D: ContextDecorator,
+    {
+        self.context_decorators.push(Arc::new(decorator));
+        self
+    }
+
+    /// Type name of the actor created by these props.
+    #[inline]
+    pub fn get_actor_type(&self) -> &'static str {
+        self.actor_type
+    }
+
+    #[inline]
+    pub(crate) fn produce(&self) -> Box<dyn Actor> {
+        (self.producer)()
+    }
+
+    pub(crate) fn with_spawner(self, spawner: Spawner) -> Self {
+        Self {
+            spawner: Some(spawner),
+            ..self
+        }
+    }
+
+    /// Props with the same middleware and decorators, spawning actors created by `producer`.
+    pub(crate) fn with_producer<A, F>(&self, producer: F) -> Self
+    where
+        A: Actor,
+        F: Fn() -> A + Send + Sync + 'static,
+    {
+        Self {
+            producer: Arc::new(move || Box::new(producer())),
+            actor_type: type_name::<A>(),
+            spawner: None,
+            ..self.clone()
+        }
+    }
+
+    /// Registers actor process under `name` and starts processing its mailbox.
+    ///
+    /// This is the last stage of the spawn pipeline, see [SpawnMiddleware].
+    pub(crate) fn spawn(
+        &self,
+        system: &Arc<ActorSystem>,
+        name: String,
+        parent: Option<Pid>,
+    ) -> Result<Pid, SpawnError> {
+        match &self.spawner {
+            Some(spawner) => spawner(self, system, name, parent),
+            None => self.spawn_process(system, name, parent, |process| process),
+        }
+    }
+
+    /// Spawns the actor, registering the process returned by `wrap` in place of the actor
+    /// process.
+    pub(crate) fn spawn_process<F>(
+        &self,
+        system: &Arc<ActorSystem>,
+        name: String,
+        parent: Option<Pid>,
+        wrap: F,
+    ) -> Result<Pid, SpawnError>
+    where
+        F: FnOnce(Arc<ActorProcess>) -> Arc<dyn Process>,
+    {
+        let (mailbox, receiver) = mailbox::unbounded();
+        let process = Arc::new(ActorProcess::new(
+            system.clone(),
+            mailbox,
+            self.actor_type,
+            parent.clone(),
+        ));
+        // started before it is registered, so it comes first also for messages a spawning
+        // process passes on
+        let pid = Pid::new(system.address().as_str(), name.as_str());
+        process.send_system_message(&pid, SystemMessage::Started);
+        let pid = system.registry().add(name, wrap(process.clone()))?;
+        if let Some(metrics) = system.metrics() {
+            metrics.actor_spawned(self.actor_type);
+        }
+
+        let ctx = ActorContext::new(system.clone(), self.clone(), pid.clone(), parent);
+        tokio::spawn(ctx.run(receiver));
+        Ok(pid)
+    }
+}
@@ -0,0 +1,499 @@
+//! Clustering groups actor systems reachable over [remote](crate::remote) into members of a
+//! cluster.
+//!
+//! [Cluster::start] starts remoting and the [ClusterProvider] of the config, which reports the
+//! current members to the [MemberList]. Every change of the topology is published on the
+//! [EventStream](crate::system::EventStream) as [MemberJoined] and [MemberLeft] for each member,
+//! followed by [ClusterTopology]. [SeedProvider] is a fixed list of members, [FileProvider]
+//! watches a JSON file of members.
+//!
+//! Grains are virtual actors addressed by [ClusterIdentity], the identity of the grain and the
+//! kind registered with [ClusterConfig::with_kind]. First request for a grain activates it on
+//! the member picked by rendezvous hashing among members hosting the kind, later requests go
+//! to the cached [Pid]. Once the topology moves a grain to another member, its cached [Pid]s
+//! are dropped and the previous owner stops its activation. Idle grains are stopped after
+//! [ClusterConfig::with_passivation] and activated again by the next request. Members that see
+//! different topologies may activate the same grain twice until their providers agree.
+//!
+//! Members share versioned key-value state through [Gossip], set with [Gossip::set_state] and
+//! spread to random members every [ClusterConfig::with_gossip] interval. Along with user keys
+//! each member gossips its [HEARTBEAT_KEY] and [TOPOLOGY_KEY]. [Gossip::get_consensus] tells
+//! whether all members agree on a key, e.g. to switch over once every member sees the same
+//! topology.
+//!
+//! ```no_run
+//! use protoactor::actor::{Actor, Props};
+//! use protoactor::cluster::{Cluster, ClusterConfig, Member, SeedProvider};
+//! use protoactor::context::ActorContext;
+//! use protoactor::remote::RemoteConfig;
+//! use protoactor::system::ActorSystem;
+//!
+//! struct User;
+//!
+//! #[async_trait::async_trait]
+//! impl Actor for User {
+//!     async fn receive(&mut self, ctx: &mut ActorContext) {}
+//! }
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() -> std::io::Result<()> {
+//! let system = ActorSystem::new();
+//! let seeds = vec![
+//!     Member::new("10.0.0.1:8090", vec!["user".to_string()]),
+//!     Member::new("10.0.0.2:8090", vec!["user".to_string()]),
+//! ];
+//! let config = ClusterConfig::new(SeedProvider::new(seeds))
+//!     .with_kind("user", Props::from_producer(|| User));
+//! let cluster = Cluster::start(&system, RemoteConfig::bind("10.0.0.1", 8090), config).await?;
+//! let user = cluster.get("alice", "user").await;
+//! # Ok(())
+//! # }
+//! ```
+mod activator;
+mod config;
+mod gossip;
+mod identity;
+mod member_list;
+mod protos;
+mod provider;
+mod rendezvous;
+
+pub use config::*;
+pub use gossip::*;
+pub use identity::*;
+pub use member_list::*;
+pub use provider::*;
+
+use crate::cluster::activator::{PartitionActivator, PARTITION_ACTIVATOR};
+use crate::cluster::gossip::GOSSIP;
+use crate::cluster::protos::{GossipRequest, GossipResponse};
+use crate::context::SenderContext;
+use crate::message::{Message, Pid};
+use crate::process::RequestError;
+use crate::remote::protos::ActorPidRequest;
+use crate::remote::{Remote, RemoteConfig, RemoteSpawnError};
+use crate::system::{ActorSystem, Subscription};
+use log::info;
+use std::collections::HashMap;
+use std::io;
+use std::sync::{Arc, Mutex, Weak};
+use tokio::task::JoinHandle;
+
+/// Local member of the cluster.
+pub struct Cluster {
+    system: Weak<ActorSystem>,
+    remote: Arc<Remote>,
+    config: ClusterConfig,
+    member_list: Arc<MemberList>,
+    /// Activations of grains, removed once the topology changes the owner of their identity.
+    pids: Arc<Mutex<HashMap<ClusterIdentity, Pid>>>,
+    subscription: Mutex<Option<Subscription>>,
+    gossip: Arc<Gossip>,
+    gossip_task: JoinHandle<()>,
+}
+
+impl Cluster {
+    /// Starts remoting with the `remote` config and joins the cluster.
+    pub async fn start(
+        system: &Arc<ActorSystem>,
+        remote: RemoteConfig,
+        config: ClusterConfig,
+    ) -> io::Result<Arc<Self>> {
+        let remote = remote
+            .with_message::<GossipRequest>("cluster.GossipRequest")
+            .with_message::<GossipResponse>("cluster.GossipResponse");
+        let remote = Remote::start(system, remote).await?;
+        let activator = Pid::new(system.address(), PARTITION_ACTIVATOR);
+        system.registry().remove(&activator);
+        let partition_activator = Arc::new(PartitionActivator::new(
+            system,
+            config.kinds.clone(),
+            config.idle_timeout,
+        ));
+        let _ = system
+            .registry()
+            .add(activator.id.clone(), partition_activator.clone());
+        let pids = Arc::new(Mutex::new(HashMap::<ClusterIdentity, Pid>::new()));
+        let activations = Arc::downgrade(&pids);
+        let partition_activator = Arc::downgrade(&partition_activator);
+        let local_address = remote.address().to_string();
+        let subscription = system
+            .event_stream()
+            .subscribe_to(move |topology: &ClusterTopology| {
+                if let Some(activations) = activations.upgrade() {
+                    activations.lock().unwrap().retain(|identity, pid| {
+                        rendezvous::get_owner(&topology.members, identity)
+                            .is_some_and(|owner| owner.address == pid.address)
+                    });
+                }
+                if let Some(partition_activator) = partition_activator.upgrade() {
+                    partition_activator.topology_changed(&topology.members, &local_address);
+                }
+            });
+        let member = Member::new(remote.address(), config.get_kinds());
+        let member_list = Arc::new(MemberList::new(system, member));
+        let gossip = Arc::new(Gossip::new(
+            system,
+            member_list.clone(),
+            config.gossip_fanout,
+        ));
+        let gossip_pid = Pid::new(system.address(), GOSSIP);
+        system.registry().remove(&gossip_pid);
+        let _ = system.registry().add(gossip_pid.id.clone(), gossip.clone());
+        if let Err(error) = config.provider.start(member_list.clone()).await {
+            system.event_stream().unsubscribe(subscription);
+            system.registry().remove(&gossip_pid);
+            system.registry().remove(&activator);
+            remote.shutdown();
+            return Err(error);
+        }
+        let gossip_task = tokio::spawn(gossip.clone().run(config.gossip_interval));
+        info!("Cluster member {} started", remote.address());
+        Ok(Arc::new(Self {
+            system: Arc::downgrade(system),
+            remote,
+            config,
+            member_list,
+            pids,
+            subscription: Mutex::new(Some(subscription)),
+            gossip,
+            gossip_task,
+        }))
+    }
+
+    pub fn remote(&self) -> &Arc<Remote> {
+        &self.remote
+    }
+
+    pub fn member_list(&self) -> &Arc<MemberList> {
+        &self.member_list
+    }
+
+    pub fn gossip(&self) -> &Arc<Gossip> {
+        &self.gossip
+    }
+
+    /// [Pid] of the grain, activating it on the member that owns the identity if it is not
+    /// active yet. Owner is picked by rendezvous hashing among members hosting the kind.
+    pub async fn get(&self, identity: &str, kind: &str) -> Result<Pid, ClusterError> {
+        let identity = ClusterIdentity::new(identity, kind);
+        if let Some(pid) = self.pids.lock().unwrap().get(&identity) {
+            return Ok(pid.clone());
+        }
+        let members = self.member_list.get_members();
+        let owner = rendezvous::get_owner(&members, &identity)
+            .ok_or_else(|| ClusterError::NoMember(identity.kind.clone()))?;
+        let system = self
+            .system
+            .upgrade()
+            .ok_or(ClusterError::Activation(RemoteSpawnError::Unavailable))?;
+        let activator = Pid::new(owner.address.as_str(), PARTITION_ACTIVATOR);
+        let request = ActorPidRequest {
+            name: identity.identity.clone(),
+            kind: identity.kind.clone(),
+        };
+        let pid = match system.root().request_future(&activator, request).await {
+            Ok(response) => RemoteSpawnError::from_response(kind, response),
+            Err(RequestError::Timeout) => Err(RemoteSpawnError::Timeout),
+            Err(_) => Err(RemoteSpawnError::Unavailable),
+        }
+        .map_err(ClusterError::Activation)?;
+        self.pids.lock().unwrap().insert(identity, pid.clone());
+        Ok(pid)
+    }
+
+    /// Requests the grain, activating it if needed. Request is sent once more to a new
+    /// activation if the grain turns out to be passivated or stopped. If the request times out
+    /// or the member of the grain is unavailable, the cached [Pid] is dropped so the next
+    /// request looks up the owner again.
+    pub async fn request<M>(
+        &self,
+        identity: &str,
+        kind: &str,
+        message: M,
+    ) -> Result<M::Result, ClusterError>
+    where
+        M: Message + Clone + Send + Sync + 'static,
+        M::Result: Send + Sync + 'static,
+    {
+        let system = self
+            .system
+            .upgrade()
+            .ok_or(ClusterError::Activation(RemoteSpawnError::Unavailable))?;
+        let pid = self.get(identity, kind).await?;
+        let evict = || {
+            self.pids
+                .lock()
+                .unwrap()
+                .retain(|_, activation| *activation != pid);
+        };
+        match system.root().request_future(&pid, message.clone()).await {
+            Err(RequestError::DeadLetter(_)) => {
+                evict();
+                let pid = self.get(identity, kind).await?;
+                let response = system.root().request_future(&pid, message).await;
+                response.map_err(ClusterError::Request)
+            }
+            Err(error @ (RequestError::Timeout | RequestError::Blocked(_))) => {
+                evict();
+                Err(ClusterError::Request(error))
+            }
+            response => response.map_err(ClusterError::Request),
+        }
+    }
+
+    /// Stops the provider and remoting, other members learn about it from their providers.
+    pub async fn shutdown(&self) {
+        self.config.provider.shutdown().await;
+        self.gossip_task.abort();
+        if let Some(system) = self.system.upgrade() {
+            if let Some(subscription) = self.subscription.lock().unwrap().take() {
+                system.event_stream().unsubscribe(subscription);
+            }
+            let activator = Pid::new(self.remote.address(), PARTITION_ACTIVATOR);
+            system.registry().remove(&activator);
+            let gossip = Pid::new(self.remote.address(), GOSSIP);
+            system.registry().remove(&gossip);
+        }
+        self.remote.shutdown();
+    }
+}
+
+impl Drop for Cluster {
+    fn drop(&mut self) {
+        self.gossip_task.abort();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actor::Props;
+    use crate::cluster::activator::PARTITION_ACTIVATOR;
+    use crate::cluster::gossip::GOSSIP;
+    use crate::cluster::rendezvous::get_owner;
+    use crate::cluster::{
+        Cluster, ClusterConfig, ClusterError, ClusterIdentity, ClusterTopology, FileProvider,
+        Member, SeedProvider, HEARTBEAT_KEY, TOPOLOGY_KEY,
+    };
+    use crate::message::Pid;
+    use crate::process::RequestError;
+    use crate::remote::tests::{config, new_system, Echo, Ping};
+    use crate::system::ActorSystem;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    fn ping() -> Ping {
+        Ping {
+            text: "ping".into(),
+        }
+    }
+
+    async fn start_member(config: ClusterConfig) -> (Arc<ActorSystem>, Arc<Cluster>) {
+        let system = new_system();
+        let config = config.with_kind("echo", Props::from_producer(|| Echo));
+        let cluster = Cluster::start(&system, super::tests::config(0), config)
+            .await
+            .unwrap();
+        (system, cluster)
+    }
+
+    #[tokio::test]
+    async fn should_activate_grains_on_owning_member() {
+        let (_system_a, cluster_a) =
+            start_member(ClusterConfig::new(SeedProvider::new(vec![]))).await;
+        let (_system_b, cluster_b) =
+            start_member(ClusterConfig::new(SeedProvider::new(vec![]))).await;
+        cluster_a
+            .member_list()
+            .update(cluster_b.member_list().get_members());
+        cluster_b
+            .member_list()
+            .update(cluster_a.member_list().get_members());
+        let members = cluster_a.member_list().get_members();
+
+        for index in 0..10 {
+            let identity = ClusterIdentity::new(index.to_string(), "echo");
+            let owner = get_owner(&members, &identity).unwrap();
+
+            let pong = cluster_a.request(&identity.identity, "echo", ping()).await;
+
+            assert!(pong.unwrap().text.starts_with("ping"));
+            let pid = cluster_a.get(&identity.identity, "echo").await.unwrap();
+            assert_eq!(owner.address, pid.address);
+            assert_eq!(Ok(pid), cluster_b.get(&identity.identity, "echo").await);
+        }
+        let unknown = cluster_a.get("0", "order").await;
+        assert_eq!(Err(ClusterError::NoMember("order".into())), unknown);
+    }
+
+    #[tokio::test]
+    async fn should_move_grains_to_joining_owner() {
+        let (system_a, cluster_a) =
+            start_member(ClusterConfig::new(SeedProvider::new(vec![]))).await;
+        let (_system_b, cluster_b) =
+            start_member(ClusterConfig::new(SeedProvider::new(vec![]))).await;
+        let identities = (0..10)
+            .map(|index| ClusterIdentity::new(index.to_string(), "echo"))
+            .collect::<Vec<_>>();
+        let mut activations = Vec::new();
+        for identity in &identities {
+            let pid = cluster_a.get(&identity.identity, "echo").await.unwrap();
+            assert_eq!(cluster_a.remote().address(), pid.address);
+            activations.push(pid);
+        }
+
+        let members = vec![
+            cluster_a.member_list().get_local_member().clone(),
+            cluster_b.member_list().get_local_member().clone(),
+        ];
+        cluster_a.member_list().update(members.clone());
+        cluster_b.member_list().update(members.clone());
+
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        for (identity, activation) in identities.iter().zip(activations) {
+            let owner = get_owner(&members, identity).unwrap();
+            let pid = cluster_a.get(&identity.identity, "echo").await.unwrap();
+            assert_eq!(owner.address, pid.address);
+            let active = system_a.registry().get(&activation).is_some();
+            assert_eq!(owner.address == members[0].address, active);
+        }
+        let owners = identities
+            .iter()
+            .map(|identity| get_owner(&members, identity));
+        assert!(owners
+            .flatten()
+            .any(|owner| owner.address == members[1].address));
+    }
+
+    #[tokio::test]
+    async fn should_drop_cached_grain_of_unavailable_member() {
+        let (_system_a, cluster_a) =
+            start_member(ClusterConfig::new(SeedProvider::new(vec![]))).await;
+        let (_system_b, cluster_b) =
+            start_member(ClusterConfig::new(SeedProvider::new(vec![]))).await;
+        let members = vec![
+            cluster_a.member_list().get_local_member().clone(),
+            cluster_b.member_list().get_local_member().clone(),
+        ];
+        cluster_a.member_list().update(members.clone());
+        let identity = (0..)
+            .map(|index| ClusterIdentity::new(index.to_string(), "echo"))
+            .find(|identity| get_owner(&members, identity) == Some(&members[1]))
+            .unwrap();
+        cluster_a
+            .request(&identity.identity, "echo", ping())
+            .await
+            .unwrap();
+
+        cluster_a.remote().block(cluster_b.remote().address());
+
+        let blocked = cluster_a.request(&identity.identity, "echo", ping()).await;
+        assert!(matches!(
+            blocked,
+            Err(ClusterError::Request(RequestError::Blocked(_)))
+        ));
+        assert!(!cluster_a.pids.lock().unwrap().contains_key(&identity));
+    }
+
+    #[tokio::test]
+    async fn should_activate_passivated_grain_again() {
+        let config = ClusterConfig::new(SeedProvider::new(vec![]))
+            .with_passivation(Duration::from_millis(100));
+        let (system, cluster) = start_member(config).await;
+        cluster.request("a", "echo", ping()).await.unwrap();
+        let pid = cluster.get("a", "echo").await.unwrap();
+
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        cluster.request("a", "echo", ping()).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(system.registry().get(&pid).is_some());
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(system.registry().get(&pid).is_none());
+
+        let pong = cluster.request("a", "echo", ping()).await;
+        assert!(pong.unwrap().text.starts_with("ping"));
+        assert!(system.registry().get(&pid).is_some());
+    }
+
+    #[tokio::test]
+    async fn should_reach_consensus_through_gossip() {
+        let mut clusters = Vec::new();
+        for _ in 0..3 {
+            let config = ClusterConfig::new(SeedProvider::new(vec![]))
+                .with_gossip(Duration::from_millis(20), 1);
+            clusters.push(start_member(config).await);
+        }
+        let members = clusters
+            .iter()
+            .map(|(_, cluster)| cluster.member_list().get_local_member().clone())
+            .collect::<Vec<_>>();
+        for (_, cluster) in &clusters {
+            cluster.member_list().update(members.clone());
+            cluster.gossip().set_state("color", b"blue".to_vec());
+        }
+        let consensus = |index: usize, key: &'static str| {
+            let gossip = clusters[index].1.gossip().clone();
+            tokio::time::timeout(Duration::from_secs(5), async move {
+                gossip.wait_for_consensus(key).await
+            })
+        };
+
+        assert_eq!(b"blue".to_vec(), consensus(0, "color").await.unwrap());
+        let topology_hash = clusters[2].1.member_list().get_topology_hash();
+        let topology = consensus(2, TOPOLOGY_KEY).await.unwrap();
+        assert_eq!(topology_hash.to_be_bytes().to_vec(), topology);
+
+        clusters[0].1.gossip().set_state("color", b"green".to_vec());
+        assert_eq!(None, clusters[0].1.gossip().get_consensus("color"));
+        clusters[1].1.gossip().set_state("color", b"green".to_vec());
+        clusters[2].1.gossip().set_state("color", b"green".to_vec());
+        assert_eq!(b"green".to_vec(), consensus(1, "color").await.unwrap());
+        assert_eq!(b"green".to_vec(), consensus(2, "color").await.unwrap());
+        let colors = clusters[2].1.gossip().get_state("color");
+        assert_eq!(3, colors.len());
+        assert!(colors.values().all(|color| color == b"green"));
+        assert_eq!(3, clusters[2].1.gossip().get_state(HEARTBEAT_KEY).len());
+    }
+
+    #[tokio::test]
+    async fn should_join_cluster_of_seeds() {
+        let system = new_system();
+        let (sender, mut topologies) = mpsc::unbounded_channel();
+        system
+            .event_stream()
+            .subscribe_to(move |topology: &ClusterTopology| {
+                let _ = sender.send(topology.clone());
+            });
+        let seed = Member::new("127.0.0.1:1", vec!["user".to_string()]);
+        let provider = SeedProvider::new(vec![seed.clone()]);
+
+        let cluster = Cluster::start(&system, config(0), ClusterConfig::new(provider))
+            .await
+            .unwrap();
+
+        let local = cluster.member_list().get_local_member().clone();
+        assert_eq!(cluster.remote().address(), local.address);
+        let topology = topologies.recv().await.unwrap();
+        assert_eq!(vec![seed.clone()], topology.joined);
+        assert_eq!(vec![seed, local], topology.members);
+        cluster.shutdown().await;
+    }
+
+    #[tokio::test]
+    async fn should_stop_remote_when_provider_fails_to_start() {
+        let system = new_system();
+        let provider = FileProvider::new("/nonexistent/members.json");
+
+        let cluster = Cluster::start(&system, config(0), ClusterConfig::new(provider)).await;
+
+        assert!(cluster.is_err());
+        for id in [PARTITION_ACTIVATOR, GOSSIP] {
+            let pid = Pid::new(system.address(), id);
+            assert!(system.registry().get(&pid).is_none());
+        }
+        let address = system.address();
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(std::net::TcpListener::bind(address).is_ok());
+    }
+}
@@ -0,0 +1,191 @@
+use crate::actor::Props;
+use crate::cluster::rendezvous::get_owner;
+use crate::cluster::{ClusterIdentity, Member};
+use crate::context::ActorContext;
+use crate::message::{AnyMessage, MessageEnvelope, Pid, Started, Stopped, SystemMessage};
+use crate::middleware::{ReceiverMiddleware, ReceiverNext};
+use crate::process::{Process, SpawnError};
+use crate::remote::protos::{ActorPidRequest, ActorPidResponse, ResponseStatusCode};
+use crate::system::ActorSystem;
+use async_trait::async_trait;
+use log::debug;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+/// Id of the process that activates grains on request of cluster members.
+pub(crate) const PARTITION_ACTIVATOR: &str = "partition-activator";
+
+/// Activates grains of the kinds hosted by the local member and responds with
+/// [ActorPidResponse]. A grain is spawned under a name derived from its identity, so
+/// requests for an active grain get the [Pid] of the existing activation.
+pub(crate) struct PartitionActivator {
+    system: Weak<ActorSystem>,
+    kinds: HashMap<String, Props>,
+    /// Activations by identity, stopped once another member owns them.
+    activations: Mutex<HashMap<ClusterIdentity, Pid>>,
+}
+
+impl PartitionActivator {
+    pub(crate) fn new(
+        system: &Arc<ActorSystem>,
+        kinds: HashMap<String, Props>,
+        idle_timeout: Option<Duration>,
+    ) -> Self {
+        let kinds = kinds
+            .into_iter()
+            .map(|(kind, props)| match idle_timeout {
+                Some(idle_timeout) => {
+                    let passivation = Passivation::new(system, idle_timeout);
+                    (kind, props.with_receiver_middleware(passivation))
+                }
+                None => (kind, props),
+            })
+            .collect();
+        Self {
+            system: Arc::downgrade(system),
+            kinds,
+            activations: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Stops activations of grains that another of the `members` owns, and forgets those
+    /// that stopped on their own.
+    pub(crate) fn topology_changed(&self, members: &[Member], local_address: &str) {
+        let Some(system) = self.system.upgrade() else {
+            return;
+        };
+        let mut moved = Vec::new();
+        self.activations.lock().unwrap().retain(|identity, pid| {
+            let owned =
+                get_owner(members, identity).is_some_and(|owner| owner.address == local_address);
+            if !owned {
+                moved.push(pid.clone());
+            }
+            owned && system.registry().get(pid).is_some()
+        });
+        for pid in moved {
+            debug!("Stopping grain {} owned by another member", pid);
+            system.get_process(&pid).stop(&pid);
+        }
+    }
+
+    fn activate(&self, system: &Arc<ActorSystem>, request: &ActorPidRequest) -> ActorPidResponse {
+        let Some(props) = self.kinds.get(&request.kind) else {
+            return ActorPidResponse {
+                pid: None,
+                status_code: ResponseStatusCode::Unknownkind as i32,
+            };
+        };
+        let name = format!("{}/{}", request.kind, request.name);
+        let pid = match props.spawn(system, name, None) {
+            Ok(pid) | Err(SpawnError::NameExists(pid)) => pid,
+        };
+        let identity = ClusterIdentity::new(request.name.as_str(), request.kind.as_str());
+        self.activations
+            .lock()
+            .unwrap()
+            .insert(identity, pid.clone());
+        ActorPidResponse {
+            pid: Some(pid),
+            status_code: ResponseStatusCode::Ok as i32,
+        }
+    }
+}
+
+impl Process for PartitionActivator {
+    fn system(&self) -> Arc<ActorSystem> {
+        self.system
+            .upgrade()
+            .expect("partition activator outlived its actor system")
+    }
+
+    fn send_user_message(&self, _pid: &Pid, envelope: MessageEnvelope<AnyMessage>) {
+        let Some(request) = envelope.get_message().downcast_ref::<ActorPidRequest>() else {
+            return debug!("Partition activator ignored {:?}", envelope.get_message());
+        };
+        let system = self.system();
+        let response = self.activate(&system, request);
+        debug!(
+            "Activated {}/{} as {:?}",
+            request.kind, request.name, response.pid
+        );
+        if let Some(sender) = envelope.get_sender() {
+            system
+                .get_process(sender)
+                .send_user_message(sender, MessageEnvelope::wrap(AnyMessage::new(response)));
+        }
+    }
+
+    fn send_system_message(&self, _pid: &Pid, _msg: SystemMessage) {}
+}
+
+/// Stops grains that received no message for the idle timeout.
+struct Passivation {
+    system: Weak<ActorSystem>,
+    idle_timeout: Duration,
+    /// Time of the last message, by grain.
+    last_messages: Mutex<HashMap<Pid, Arc<Mutex<Instant>>>>,
+}
+
+impl Passivation {
+    fn new(system: &Arc<ActorSystem>, idle_timeout: Duration) -> Self {
+        Self {
+            system: Arc::downgrade(system),
+            idle_timeout,
+            last_messages: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Stops the grain once it stays idle for the timeout, or returns once it stopped.
+    async fn passivate(
+        system: Weak<ActorSystem>,
+        pid: Pid,
+        last_message: Weak<Mutex<Instant>>,
+        idle_timeout: Duration,
+    ) {
+        loop {
+            let Some(last_message) = last_message.upgrade().map(|last| *last.lock().unwrap())
+            else {
+                return;
+            };
+            if last_message.elapsed() >= idle_timeout {
+                break;
+            }
+            tokio::time::sleep_until((last_message + idle_timeout).into()).await;
+        }
+        if let Some(system) = system.upgrade() {
+            debug!("Passivating idle grain {}", pid);
+            system.get_process(&pid).stop(&pid);
+        }
+    }
+}
+
+#[async_trait]
+impl ReceiverMiddleware for Passivation {
+    async fn receive(
+        &self,
+        ctx: &mut ActorContext,
+        envelope: MessageEnvelope<AnyMessage>,
+        next: ReceiverNext<'_>,
+    ) {
+        let pid = ctx.get_self().clone();
+        let message = envelope.get_message();
+        if message.is::<Started>() {
+            let last_message = Arc::new(Mutex::new(Instant::now()));
+            let passivate = Self::passivate(
+                self.system.clone(),
+                pid.clone(),
+                Arc::downgrade(&last_message),
+                self.idle_timeout,
+            );
+            self.last_messages.lock().unwrap().insert(pid, last_message);
+            tokio::spawn(passivate);
+        } else if message.is::<Stopped>() {
+            self.last_messages.lock().unwrap().remove(&pid);
+        } else if let Some(last_message) = self.last_messages.lock().unwrap().get(&pid) {
+            *last_message.lock().unwrap() = Instant::now();
+        }
+        next.run(ctx, envelope).await
+    }
+}
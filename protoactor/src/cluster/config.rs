@@ -0,0 +1,66 @@
+use crate::actor::Props;
+use crate::cluster::ClusterProvider;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Configuration of [Cluster](crate::cluster::Cluster).
+#[derive(Clone)]
+pub struct ClusterConfig {
+    pub(crate) provider: Arc<dyn ClusterProvider>,
+    pub(crate) kinds: HashMap<String, Props>,
+    pub(crate) idle_timeout: Option<Duration>,
+    pub(crate) gossip_interval: Duration,
+    pub(crate) gossip_fanout: usize,
+}
+
+impl ClusterConfig {
+    /// Cluster with members reported by the `provider`.
+    pub fn new<P>(provider: P) -> Self
+    where
+        P: ClusterProvider + 'static,
+    {
+        Self {
+            provider: Arc::new(provider),
+            kinds: HashMap::new(),
+            idle_timeout: None,
+            gossip_interval: Duration::from_millis(300),
+            gossip_fanout: 3,
+        }
+    }
+
+    /// Hosts grains of `kind` on the local member, activated from the `props`.
+    pub fn with_kind<K>(mut self, kind: K, props: Props) -> Self
+    where
+        K: Into<String>,
+    {
+        self.kinds.insert(kind.into(), props);
+        self
+    }
+
+    /// Stops grains that received no message for the `idle_timeout`, the next request
+    /// activates them again. Grains stay active until stopped by default.
+    pub fn with_passivation(self, idle_timeout: Duration) -> Self {
+        Self {
+            idle_timeout: Some(idle_timeout),
+            ..self
+        }
+    }
+
+    /// Gossips the state to `fanout` random members every `interval`, 3 members every 300ms
+    /// by default.
+    pub fn with_gossip(self, interval: Duration, fanout: usize) -> Self {
+        Self {
+            gossip_interval: interval,
+            gossip_fanout: fanout,
+            ..self
+        }
+    }
+
+    /// Kinds hosted by the local member, sorted by name.
+    pub(crate) fn get_kinds(&self) -> Vec<String> {
+        let mut kinds = self.kinds.keys().cloned().collect::<Vec<_>>();
+        kinds.sort();
+        kinds
+    }
+}
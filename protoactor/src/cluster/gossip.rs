@@ -0,0 +1,346 @@
+use crate::cluster::protos::{
+    GossipKeyValue, GossipMemberState, GossipRequest, GossipResponse, GossipState,
+};
+use crate::cluster::MemberList;
+use crate::context::SenderContext;
+use crate::message::{AnyMessage, MessageEnvelope, Pid, SystemMessage};
+use crate::process::Process;
+use crate::system::ActorSystem;
+use log::debug;
+use rand::seq::SliceRandom;
+use rand::Rng;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::watch;
+
+/// Id of the process that exchanges gossip with other members.
+pub(crate) const GOSSIP: &str = "gossip";
+
+/// Key of the time a member last gossiped, as big-endian milliseconds since the Unix epoch.
+pub const HEARTBEAT_KEY: &str = "heartbeat";
+
+/// Key of the topology hash a member sees, as big-endian
+/// [MemberList::get_topology_hash].
+pub const TOPOLOGY_KEY: &str = "topology";
+
+/// Key-value state of every member, exchanged with random members on each gossip interval
+/// until all members converge. Members only set their own values, each change gets a higher
+/// sequence number so newer values win the merge. Values are kept per incarnation of a member,
+/// those it set before restarting are dropped once its new incarnation is gossiped, and the
+/// replaced incarnation is not taken again.
+pub struct Gossip {
+    system: Weak<ActorSystem>,
+    member_list: Arc<MemberList>,
+    fanout: usize,
+    state: Mutex<GossipState>,
+    /// Sequence number of the last value set by the local member.
+    sequence_number: Mutex<i64>,
+    /// Incarnations replaced by later ones, by member address.
+    retired: Mutex<HashMap<String, HashSet<u64>>>,
+    changed: watch::Sender<()>,
+}
+
+impl Gossip {
+    pub(crate) fn new(
+        system: &Arc<ActorSystem>,
+        member_list: Arc<MemberList>,
+        fanout: usize,
+    ) -> Self {
+        let local = GossipMemberState {
+            values: HashMap::new(),
+            incarnation: rand::thread_rng().gen_range(1..=u64::MAX),
+        };
+        let address = member_list.get_local_member().address.clone();
+        Self {
+            system: Arc::downgrade(system),
+            member_list,
+            fanout,
+            state: Mutex::new(GossipState {
+                members: HashMap::from([(address, local)]),
+            }),
+            sequence_number: Mutex::new(0),
+            retired: Mutex::new(HashMap::new()),
+            changed: watch::channel(()).0,
+        }
+    }
+
+    /// Sets the value of the local member, gossiped to the other members.
+    pub fn set_state(&self, key: &str, value: Vec<u8>) {
+        {
+            let mut state = self.state.lock().unwrap();
+            let mut sequence_number = self.sequence_number.lock().unwrap();
+            *sequence_number += 1;
+            let address = &self.member_list.get_local_member().address;
+            let local = state
+                .members
+                .get_mut(address)
+                .expect("local member is kept");
+            local.values.insert(
+                key.to_string(),
+                GossipKeyValue {
+                    sequence_number: *sequence_number,
+                    value,
+                },
+            );
+        }
+        self.changed.send_replace(());
+    }
+
+    /// Values of the key, by address of the members that set it.
+    pub fn get_state(&self, key: &str) -> HashMap<String, Vec<u8>> {
+        self.state
+            .lock()
+            .unwrap()
+            .members
+            .iter()
+            .filter_map(|(address, member)| {
+                let value = member.values.get(key)?;
+                Some((address.clone(), value.value.clone()))
+            })
+            .collect()
+    }
+
+    /// Value of the key if every current member has set it to the same value.
+    pub fn get_consensus(&self, key: &str) -> Option<Vec<u8>> {
+        let members = self.member_list.get_members();
+        let state = self.state.lock().unwrap();
+        let mut values = members.iter().map(|member| {
+            let values = &state.members.get(&member.address)?.values;
+            values.get(key).map(|value| &value.value)
+        });
+        let consensus = values.next()??;
+        values
+            .all(|value| value == Some(consensus))
+            .then(|| consensus.clone())
+    }
+
+    /// Waits until every current member has set the key to the same value.
+    pub async fn wait_for_consensus(&self, key: &str) -> Vec<u8> {
+        let mut changed = self.changed.subscribe();
+        loop {
+            if let Some(consensus) = self.get_consensus(key) {
+                return consensus;
+            }
+            // Sender lives as long as self.
+            let _ = changed.changed().await;
+        }
+    }
+
+    /// Gossips on every interval until the task is aborted.
+    pub(crate) async fn run(self: Arc<Self>, interval: Duration) {
+        let mut interval = tokio::time::interval(interval);
+        interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        loop {
+            interval.tick().await;
+            self.gossip();
+        }
+    }
+
+    /// Updates the heartbeat and topology of the local member and sends the state to
+    /// `fanout` random members, merging the state they respond with.
+    fn gossip(self: &Arc<Self>) {
+        let Some(system) = self.system.upgrade() else {
+            return;
+        };
+        let topology_hash = self.member_list.get_topology_hash().to_be_bytes().to_vec();
+        let local = self.member_list.get_local_member().address.clone();
+        if self.get_state(TOPOLOGY_KEY).get(&local) != Some(&topology_hash) {
+            self.set_state(TOPOLOGY_KEY, topology_hash);
+        }
+        self.set_state(HEARTBEAT_KEY, unix_millis().to_be_bytes().to_vec());
+        let members = self.member_list.get_members();
+        let is_member = |address: &String| members.iter().any(|member| member.address == *address);
+        self.state
+            .lock()
+            .unwrap()
+            .members
+            .retain(|address, _| is_member(address));
+        self.retired
+            .lock()
+            .unwrap()
+            .retain(|address, _| is_member(address));
+
+        let others = members
+            .iter()
+            .filter(|member| member.address != local)
+            .collect::<Vec<_>>();
+        for member in others.choose_multiple(&mut rand::thread_rng(), self.fanout) {
+            let target = Pid::new(member.address.as_str(), GOSSIP);
+            let request = GossipRequest {
+                state: Some(self.state.lock().unwrap().clone()),
+            };
+            let gossip = self.clone();
+            let system = system.clone();
+            tokio::spawn(async move {
+                match system.root().request_future(&target, request).await {
+                    Ok(response) => gossip.merge(response.state.unwrap_or_default()),
+                    Err(error) => debug!("Gossip to {} failed: {:?}", target.address, error),
+                }
+            });
+        }
+    }
+
+    fn merge(&self, remote: GossipState) {
+        let local = &self.member_list.get_local_member().address;
+        let changed = {
+            let mut state = self.state.lock().unwrap();
+            let remote = GossipState {
+                members: remote
+                    .members
+                    .into_iter()
+                    .filter(|(address, _)| self.member_list.contains(address))
+                    .collect(),
+            };
+            merge(&mut state, remote, local, &mut self.retired.lock().unwrap())
+        };
+        if changed {
+            self.changed.send_replace(());
+        }
+    }
+}
+
+impl Process for Gossip {
+    fn system(&self) -> Arc<ActorSystem> {
+        self.system
+            .upgrade()
+            .expect("gossip outlived its actor system")
+    }
+
+    fn send_user_message(&self, _pid: &Pid, envelope: MessageEnvelope<AnyMessage>) {
+        let Some(request) = envelope.get_message().downcast_ref::<GossipRequest>() else {
+            return debug!("Gossip ignored {:?}", envelope.get_message());
+        };
+        self.merge(request.state.clone().unwrap_or_default());
+        let response = GossipResponse {
+            state: Some(self.state.lock().unwrap().clone()),
+        };
+        if let Some(sender) = envelope.get_sender() {
+            self.system()
+                .get_process(sender)
+                .send_user_message(sender, MessageEnvelope::wrap(AnyMessage::new(response)));
+        }
+    }
+
+    fn send_system_message(&self, _pid: &Pid, _msg: SystemMessage) {}
+}
+
+/// Takes the values of the remote state with higher sequence numbers than the local ones,
+/// except those of the local member which it alone sets. Values of another incarnation of a
+/// member replace all of its values, unless that incarnation was replaced before. Returns
+/// whether any value changed.
+fn merge(
+    state: &mut GossipState,
+    remote: GossipState,
+    local: &str,
+    retired: &mut HashMap<String, HashSet<u64>>,
+) -> bool {
+    let mut changed = false;
+    for (address, remote) in remote.members {
+        if address == local {
+            continue;
+        }
+        let retired = retired.entry(address.clone()).or_default();
+        // Members gossip their own state with an incarnation, others only pass it on.
+        if remote.incarnation == 0 || retired.contains(&remote.incarnation) {
+            continue;
+        }
+        let member = state.members.entry(address).or_default();
+        if remote.incarnation != member.incarnation {
+            if member.incarnation != 0 {
+                retired.insert(member.incarnation);
+            }
+            changed |= !member.values.is_empty();
+            *member = GossipMemberState {
+                values: HashMap::new(),
+                incarnation: remote.incarnation,
+            };
+        }
+        for (key, value) in remote.values {
+            let newer = member
+                .values
+                .get(&key)
+                .is_none_or(|current| current.sequence_number < value.sequence_number);
+            if newer {
+                member.values.insert(key, value);
+                changed = true;
+            }
+        }
+    }
+    changed
+}
+
+fn unix_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis() as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cluster::protos::{GossipKeyValue, GossipMemberState, GossipState};
+    use std::collections::HashMap;
+
+    fn merge(state: &mut GossipState, remote: GossipState, local: &str) -> bool {
+        super::merge(state, remote, local, &mut HashMap::new())
+    }
+
+    fn state(entries: &[(&str, &str, i64, &str)]) -> GossipState {
+        let mut state = GossipState::default();
+        for (address, key, sequence_number, value) in entries {
+            let member = state.members.entry(address.to_string()).or_default();
+            member.incarnation = 1;
+            let value = GossipKeyValue {
+                sequence_number: *sequence_number,
+                value: value.as_bytes().to_vec(),
+            };
+            member.values.insert(key.to_string(), value);
+        }
+        state
+    }
+
+    #[test]
+    fn should_merge_newer_values_of_other_members() {
+        let mut local = state(&[("a:1", "color", 1, "blue"), ("b:1", "color", 2, "blue")]);
+        let remote = state(&[
+            ("a:1", "color", 5, "green"),
+            ("b:1", "color", 1, "red"),
+            ("c:1", "color", 3, "green"),
+        ]);
+
+        assert!(merge(&mut local, remote.clone(), "a:1"));
+        assert!(!merge(&mut local, remote, "a:1"));
+
+        let expected = state(&[
+            ("a:1", "color", 1, "blue"),
+            ("b:1", "color", 2, "blue"),
+            ("c:1", "color", 3, "green"),
+        ]);
+        assert_eq!(expected, local);
+        let empty = GossipState {
+            members: HashMap::from([("d:1".to_string(), GossipMemberState::default())]),
+        };
+        assert!(!merge(&mut local, empty, "a:1"));
+    }
+
+    #[test]
+    fn should_replace_values_of_other_incarnations() {
+        let mut retired = HashMap::new();
+        let mut local = state(&[("b:1", "color", 5, "blue"), ("b:1", "size", 6, "large")]);
+        let mut restarted = state(&[("b:1", "color", 1, "green")]);
+        restarted.members.get_mut("b:1").unwrap().incarnation = 10;
+        let mut merge = |local: &mut GossipState, remote: GossipState| {
+            super::merge(local, remote, "a:1", &mut retired)
+        };
+
+        assert!(merge(&mut local, restarted.clone()));
+        assert_eq!(restarted, local);
+        let stale = state(&[("b:1", "size", 7, "small")]);
+        assert!(!merge(&mut local, stale));
+        assert_eq!(restarted, local);
+        let mut earlier_clock = state(&[("b:1", "color", 1, "red")]);
+        earlier_clock.members.get_mut("b:1").unwrap().incarnation = 3;
+        assert!(merge(&mut local, earlier_clock.clone()));
+        assert_eq!(earlier_clock, local);
+    }
+}
@@ -0,0 +1,48 @@
+use crate::process::RequestError;
+use crate::remote::RemoteSpawnError;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+
+/// Address of a grain, a virtual actor of `kind` that is activated on first request.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClusterIdentity {
+    pub identity: String,
+    pub kind: String,
+}
+
+impl ClusterIdentity {
+    pub fn new(identity: impl Into<String>, kind: impl Into<String>) -> Self {
+        Self {
+            identity: identity.into(),
+            kind: kind.into(),
+        }
+    }
+}
+
+impl Display for ClusterIdentity {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.kind, self.identity)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClusterError {
+    /// No member of the cluster hosts grains of the kind.
+    NoMember(String),
+    /// Owner of the grain failed to activate it.
+    Activation(RemoteSpawnError),
+    /// Grain failed to respond.
+    Request(RequestError),
+}
+
+impl Display for ClusterError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClusterError::NoMember(kind) => write!(f, "no member hosts kind {}", kind),
+            ClusterError::Activation(error) => write!(f, "activation failed: {}", error),
+            ClusterError::Request(error) => write!(f, "grain request failed: {}", error),
+        }
+    }
+}
+
+impl Error for ClusterError {}
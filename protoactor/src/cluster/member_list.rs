@@ -0,0 +1,204 @@
+use crate::message::Message;
+use crate::system::ActorSystem;
+use log::info;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock, Weak};
+
+/// Actor system taking part in the cluster.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Member {
+    /// Remote address of the actor system, unique within the cluster.
+    pub address: String,
+    /// Kinds of actors the member hosts.
+    #[serde(default)]
+    pub kinds: Vec<String>,
+}
+
+impl Member {
+    pub fn new(address: impl Into<String>, kinds: Vec<String>) -> Self {
+        Self {
+            address: address.into(),
+            kinds,
+        }
+    }
+}
+
+/// Member joined the cluster, published before the [ClusterTopology] it is part of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberJoined {
+    pub member: Member,
+}
+
+impl Message for MemberJoined {
+    type Result = ();
+}
+
+/// Member left the cluster, published before the [ClusterTopology] it is part of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemberLeft {
+    pub member: Member,
+}
+
+impl Message for MemberLeft {
+    type Result = ();
+}
+
+/// Members of the cluster after a change of the topology, ordered by address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterTopology {
+    pub members: Vec<Member>,
+    pub joined: Vec<Member>,
+    pub left: Vec<Member>,
+    /// Hash of member addresses, equal on members that see the same topology.
+    pub topology_hash: u64,
+}
+
+impl Message for ClusterTopology {
+    type Result = ();
+}
+
+/// Current members of the cluster as reported by the
+/// [ClusterProvider](crate::cluster::ClusterProvider), always including the local member.
+pub struct MemberList {
+    system: Weak<ActorSystem>,
+    local: Member,
+    members: RwLock<BTreeMap<String, Member>>,
+}
+
+impl MemberList {
+    pub(crate) fn new(system: &Arc<ActorSystem>, local: Member) -> Self {
+        let members = BTreeMap::from([(local.address.clone(), local.clone())]);
+        Self {
+            system: Arc::downgrade(system),
+            local,
+            members: RwLock::new(members),
+        }
+    }
+
+    pub fn get_local_member(&self) -> &Member {
+        &self.local
+    }
+
+    /// Members ordered by address.
+    pub fn get_members(&self) -> Vec<Member> {
+        self.members.read().unwrap().values().cloned().collect()
+    }
+
+    pub fn contains(&self, address: &str) -> bool {
+        self.members.read().unwrap().contains_key(address)
+    }
+
+    pub fn get_topology_hash(&self) -> u64 {
+        topology_hash(&self.members.read().unwrap())
+    }
+
+    /// Replaces the members with the reported ones and publishes the changes. The local member
+    /// is kept as it is, whether it is reported or not.
+    pub fn update(&self, members: Vec<Member>) {
+        let mut updated = members
+            .into_iter()
+            .map(|member| (member.address.clone(), member))
+            .collect::<BTreeMap<_, _>>();
+        updated.insert(self.local.address.clone(), self.local.clone());
+        let topology = {
+            let mut members = self.members.write().unwrap();
+            let left = members
+                .values()
+                .filter(|member| updated.get(&member.address) != Some(member))
+                .cloned()
+                .collect::<Vec<_>>();
+            let joined = updated
+                .values()
+                .filter(|member| members.get(&member.address) != Some(member))
+                .cloned()
+                .collect::<Vec<_>>();
+            if joined.is_empty() && left.is_empty() {
+                return;
+            }
+            *members = updated;
+            ClusterTopology {
+                members: members.values().cloned().collect(),
+                joined,
+                left,
+                topology_hash: topology_hash(&members),
+            }
+        };
+        let Some(system) = self.system.upgrade() else {
+            return;
+        };
+        for member in &topology.left {
+            info!("Member {} left the cluster", member.address);
+            let member = member.clone();
+            system.event_stream().publish(MemberLeft { member });
+        }
+        for member in &topology.joined {
+            info!("Member {} joined the cluster", member.address);
+            let member = member.clone();
+            system.event_stream().publish(MemberJoined { member });
+        }
+        system.event_stream().publish(topology);
+    }
+}
+
+fn topology_hash(members: &BTreeMap<String, Member>) -> u64 {
+    fnv1a(
+        members
+            .keys()
+            .flat_map(|address| address.bytes().chain([0])),
+    )
+}
+
+/// FNV-1a hash, stable across processes and versions unlike the hashers of std.
+pub(crate) fn fnv1a<I>(bytes: I) -> u64
+where
+    I: IntoIterator<Item = u8>,
+{
+    bytes.into_iter().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cluster::{Member, MemberJoined, MemberLeft, MemberList};
+    use crate::remote::tests::new_system;
+    use std::sync::{Arc, Mutex};
+
+    #[test]
+    fn should_publish_joined_and_left_members() {
+        let system = new_system();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let received = events.clone();
+        system.event_stream().subscribe(move |event| {
+            if let Some(joined) = event.downcast_ref::<MemberJoined>() {
+                received
+                    .lock()
+                    .unwrap()
+                    .push(format!("+{}", joined.member.address));
+            } else if let Some(left) = event.downcast_ref::<MemberLeft>() {
+                received
+                    .lock()
+                    .unwrap()
+                    .push(format!("-{}", left.member.address));
+            }
+        });
+        let member = |address: &str| Member::new(address, Vec::new());
+        let member_list = MemberList::new(&system, member("a:1"));
+        let initial_hash = member_list.get_topology_hash();
+
+        member_list.update(vec![member("b:1"), member("c:1")]);
+        member_list.update(vec![member("a:1"), member("c:1")]);
+        member_list.update(vec![member("c:1")]);
+
+        assert_eq!(vec!["+b:1", "+c:1", "-b:1"], *events.lock().unwrap());
+        assert!(member_list.contains("a:1"));
+        assert!(!member_list.contains("b:1"));
+        assert_eq!(
+            vec![member("a:1"), member("c:1")],
+            member_list.get_members()
+        );
+        member_list.update(Vec::new());
+        assert_eq!(initial_hash, member_list.get_topology_hash());
+    }
+}
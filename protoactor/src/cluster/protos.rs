@@ -0,0 +1,11 @@
+use crate::message::Message;
+
+include!(concat!(env!("OUT_DIR"), "/cluster/cluster.rs"));
+
+impl Message for GossipRequest {
+    type Result = GossipResponse;
+}
+
+impl Message for GossipResponse {
+    type Result = ();
+}
@@ -0,0 +1,20 @@
+mod file;
+mod seed;
+
+pub use file::*;
+pub use seed::*;
+
+use crate::cluster::MemberList;
+use async_trait::async_trait;
+use std::io;
+use std::sync::Arc;
+
+/// Discovers members of the cluster, [SeedProvider] and [FileProvider] are built in.
+#[async_trait]
+pub trait ClusterProvider: Send + Sync {
+    /// Reports the current members to the member list, and keeps reporting changes until shut
+    /// down.
+    async fn start(&self, member_list: Arc<MemberList>) -> io::Result<()>;
+
+    async fn shutdown(&self);
+}
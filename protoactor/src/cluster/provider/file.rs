@@ -0,0 +1,125 @@
+use crate::cluster::{ClusterProvider, Member, MemberList};
+use async_trait::async_trait;
+use log::warn;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Members listed in a local JSON file, such as
+/// `[{"address": "10.0.0.1:8090", "kinds": ["user"]}]`.
+///
+/// The file is polled for changes, members stay as they are while it cannot be read or
+/// parsed.
+pub struct FileProvider {
+    path: PathBuf,
+    poll_interval: Duration,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl FileProvider {
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            poll_interval: Duration::from_secs(1),
+            task: Mutex::new(None),
+        }
+    }
+
+    /// How often the file is checked for changes, every second by default.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+}
+
+#[async_trait]
+impl ClusterProvider for FileProvider {
+    async fn start(&self, member_list: Arc<MemberList>) -> io::Result<()> {
+        let mut contents = tokio::fs::read(&self.path).await?;
+        member_list.update(parse_members(&contents)?);
+        let path = self.path.clone();
+        let poll_interval = self.poll_interval;
+        let task = tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(poll_interval).await;
+                match tokio::fs::read(&path).await {
+                    Ok(changed) if changed != contents => {
+                        match parse_members(&changed) {
+                            Ok(members) => member_list.update(members),
+                            Err(error) => warn!("Invalid members in {:?}: {}", path, error),
+                        }
+                        contents = changed;
+                    }
+                    Ok(_) => {}
+                    Err(error) => warn!("Cannot read members from {:?}: {}", path, error),
+                }
+            }
+        });
+        if let Some(previous) = self.task.lock().unwrap().replace(task) {
+            previous.abort();
+        }
+        Ok(())
+    }
+
+    async fn shutdown(&self) {
+        if let Some(task) = self.task.lock().unwrap().take() {
+            task.abort();
+        }
+    }
+}
+
+impl Drop for FileProvider {
+    fn drop(&mut self) {
+        if let Some(task) = self.task.lock().unwrap().take() {
+            task.abort();
+        }
+    }
+}
+
+fn parse_members(contents: &[u8]) -> io::Result<Vec<Member>> {
+    serde_json::from_slice(contents)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cluster::{ClusterProvider, ClusterTopology, FileProvider, Member, MemberList};
+    use crate::remote::tests::new_system;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    #[tokio::test]
+    async fn should_update_members_when_file_changes() {
+        let system = new_system();
+        let (sender, mut topologies) = mpsc::unbounded_channel();
+        system
+            .event_stream()
+            .subscribe_to(move |topology: &ClusterTopology| {
+                let _ = sender.send(topology.clone());
+            });
+        let path = std::env::temp_dir().join(format!("members-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, r#"[{"address": "b:1", "kinds": ["user"]}]"#).unwrap();
+        let member_list = Arc::new(MemberList::new(&system, Member::new("a:1", Vec::new())));
+        let provider = FileProvider::new(&path).with_poll_interval(Duration::from_millis(10));
+
+        provider.start(member_list.clone()).await.unwrap();
+        let topology = topologies.recv().await.unwrap();
+        assert_eq!(
+            vec![Member::new("b:1", vec!["user".into()])],
+            topology.joined
+        );
+
+        std::fs::write(&path, "[{\"address\": \"b:1\"").unwrap();
+        std::fs::write(&path, r#"[{"address": "c:1"}]"#).unwrap();
+        let topology = topologies.recv().await.unwrap();
+        provider.shutdown().await;
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(vec![Member::new("c:1", Vec::new())], topology.joined);
+        assert_eq!("b:1", topology.left[0].address);
+        assert!(member_list.contains("a:1"));
+    }
+}
@@ -0,0 +1,25 @@
+use crate::cluster::{ClusterProvider, Member, MemberList};
+use async_trait::async_trait;
+use std::io;
+use std::sync::Arc;
+
+/// Fixed list of members, for clusters deployed as a known set of actor systems.
+pub struct SeedProvider {
+    seeds: Vec<Member>,
+}
+
+impl SeedProvider {
+    pub fn new(seeds: Vec<Member>) -> Self {
+        Self { seeds }
+    }
+}
+
+#[async_trait]
+impl ClusterProvider for SeedProvider {
+    async fn start(&self, member_list: Arc<MemberList>) -> io::Result<()> {
+        member_list.update(self.seeds.clone());
+        Ok(())
+    }
+
+    async fn shutdown(&self) {}
+}
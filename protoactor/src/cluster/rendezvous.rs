@@ -0,0 +1,61 @@
+use crate::cluster::member_list::fnv1a;
+use crate::cluster::{ClusterIdentity, Member};
+
+/// Member hosting the kind of the grain with the highest score for the identity, so that
+/// members joining or leaving only move grains from or to themselves.
+pub(crate) fn get_owner<'a>(
+    members: &'a [Member],
+    identity: &ClusterIdentity,
+) -> Option<&'a Member> {
+    members
+        .iter()
+        .filter(|member| member.kinds.contains(&identity.kind))
+        .max_by_key(|member| {
+            let key = [&member.address, &identity.kind, &identity.identity];
+            let score = fnv1a(key.iter().flat_map(|part| part.bytes().chain([0])));
+            (mix(score), &member.address)
+        })
+}
+
+/// Finalizer of MurmurHash3, spreads hashes of keys that differ in their last bytes only.
+fn mix(mut hash: u64) -> u64 {
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    hash ^= hash >> 33;
+    hash = hash.wrapping_mul(0xc4ce_b9fe_1a85_ec53);
+    hash ^ (hash >> 33)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::cluster::rendezvous::get_owner;
+    use crate::cluster::{ClusterIdentity, Member};
+
+    #[test]
+    fn should_move_only_grains_of_leaving_member() {
+        let member = |address: &str| Member::new(address, vec!["user".into()]);
+        let members = vec![member("a:1"), member("b:1"), member("c:1")];
+        let identities = (0..100)
+            .map(|index| ClusterIdentity::new(index.to_string(), "user"))
+            .collect::<Vec<_>>();
+        let owners = |members: &[Member]| {
+            identities
+                .iter()
+                .map(|identity| get_owner(members, identity).unwrap().address.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let before = owners(&members);
+        let after = owners(&members[..2]);
+
+        for owner in ["a:1", "b:1", "c:1"] {
+            let owned = before.iter().filter(|address| *address == owner).count();
+            assert!(owned > 20, "{} owns {} grains", owner, owned);
+        }
+        for (before, after) in before.iter().zip(&after) {
+            assert!(before == after || before == "c:1");
+        }
+        let other_kind = ClusterIdentity::new("0", "order");
+        assert_eq!(None, get_owner(&members, &other_kind));
+    }
+}
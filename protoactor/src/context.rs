@@ -1,15 +1,57 @@
 mod actor_context;
+mod continuation;
+mod decorator;
+mod root_context;
 
 pub use actor_context::*;
+pub use continuation::*;
+pub use decorator::*;
+pub use root_context::*;
 
-use crate::message::MessageHeader;
+use crate::message::{AnyMessage, Message, MessageEnvelope, MessageHeader, Pid};
+use crate::process::{FutureProcess, RequestFuture};
+use crate::system::ActorSystem;
+use std::sync::Arc;
 
 pub trait SenderContext {
-    type Message;
+    fn get_system(&self) -> &Arc<ActorSystem>;
 
     /// [MessageHeader] of the context.
     fn get_headers(&self) -> MessageHeader;
-    fn get_message(&self) -> Self::Message;
+
+    /// Delivers envelope to the target process.
+    fn send_envelope(&self, target: &Pid, envelope: MessageEnvelope<AnyMessage>);
+
+    /// Sends message to the target process without waiting for the result.
+    fn send<M>(&self, target: &Pid, message: M)
+    where
+        Self: Sized,
+        M: Message + Send + Sync + 'static,
+    {
+        self.send_envelope(target, MessageEnvelope::wrap(AnyMessage::new(message)))
+    }
+
+    /// Sends message to the target process and returns future that resolves with the response.
+    ///
+    /// Future fails with [RequestError::Timeout](crate::process::RequestError::Timeout) if
+    /// response does not arrive within configured actor request timeout. Returned future does
+    /// not borrow the context, so it can be passed to
+    /// [ActorContext::reenter_after].
+    fn request_future<M>(&self, target: &Pid, message: M) -> RequestFuture<M::Result>
+    where
+        Self: Sized,
+        M: Message + Send + Sync + 'static,
+        M::Result: Send + Sync + 'static,
+    {
+        let system = self.get_system();
+        let timeout = system.config().actor_request_timeout;
+        let (future_pid, future) = FutureProcess::spawn(system, timeout);
+        self.send_envelope(
+            target,
+            MessageEnvelope::new(AnyMessage::new(message), Some(future_pid), None),
+        );
+        future
+    }
 }
 
 pub trait ReceiverContext {
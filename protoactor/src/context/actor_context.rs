@@ -1,9 +1,733 @@
-use crate::message::{Message, MessageEnvelope};
-
-pub struct ActorContext<M>
-where
-    M: Message + Send + 'static,
-    M::Result: Send,
-{
-    message: MessageEnvelope<M>,
+use crate::actor::{Actor, Props};
+use crate::context::{ContextNext, Continuation, SenderContext};
+use crate::diagnostics::{ActorDiagnostics, DiagnosticsTypeName};
+use crate::mailbox::{MailboxMessage, MailboxReceiver};
+use crate::message::{
+    AnyMessage, Message, MessageEnvelope, MessageHeader, Pid, PoisonPill, Started, Stopped,
+    Stopping, SystemMessage, Terminated, TerminatedReason, Unwatch, Watch,
+};
+use crate::middleware::{self, ReceiverNext, SendQueue};
+use crate::process::{FutureProcess, RequestFuture, SpawnError};
+use crate::system::ActorSystem;
+#[cfg(feature = "tracing")]
+use crate::trace::{ReceiveTrace, TraceContext, TRACEPARENT_HEADER};
+use log::warn;
+use std::any::{type_name, Any};
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Context of a single actor, passed to [Actor::receive] along with every message.
+pub struct ActorContext {
+    system: Arc<ActorSystem>,
+    props: Props,
+    self_pid: Pid,
+    parent: Option<Pid>,
+    children: HashSet<Pid>,
+    watchers: HashSet<Pid>,
+    actor: Option<Box<dyn Actor>>,
+    message: MessageEnvelope<AnyMessage>,
+    sends: SendQueue,
+    /// Receive span of the user message that is currently being processed.
+    #[cfg(feature = "tracing")]
+    trace: Option<ReceiveTrace>,
+    stopping: bool,
+}
+
+impl ActorContext {
+    pub(crate) fn new(
+        system: Arc<ActorSystem>,
+        props: Props,
+        self_pid: Pid,
+        parent: Option<Pid>,
+    ) -> Self {
+        let actor = props.produce();
+        Self {
+            system,
+            props,
+            self_pid,
+            parent,
+            children: Default::default(),
+            watchers: Default::default(),
+            actor: Some(actor),
+            message: MessageEnvelope::wrap(AnyMessage::new(Started)),
+            sends: SendQueue::default(),
+            #[cfg(feature = "tracing")]
+            trace: None,
+            stopping: false,
+        }
+    }
+
+    #[inline]
+    pub fn get_self(&self) -> &Pid {
+        &self.self_pid
+    }
+
+    #[inline]
+    pub fn get_parent(&self) -> Option<&Pid> {
+        self.parent.as_ref()
+    }
+
+    #[inline]
+    pub fn get_props(&self) -> &Props {
+        &self.props
+    }
+
+    pub fn get_children(&self) -> impl Iterator<Item = &Pid> {
+        self.children.iter()
+    }
+
+    /// Message that is currently being processed.
+    #[inline]
+    pub fn get_message(&self) -> AnyMessage {
+        self.message.get_message().clone()
+    }
+
+    #[inline]
+    pub fn get_sender(&self) -> Option<&Pid> {
+        self.message.get_sender().as_ref()
+    }
+
+    /// Envelope of the message that is currently being processed.
+    #[inline]
+    pub fn get_envelope(&self) -> &MessageEnvelope<AnyMessage> {
+        &self.message
+    }
+
+    /// Sends message to the target and sets this actor as sender, so the response is delivered
+    /// to this actor's mailbox.
+    pub fn request<M>(&self, target: &Pid, message: M)
+    where
+        M: Message + Send + Sync + 'static,
+    {
+        let envelope =
+            MessageEnvelope::new(AnyMessage::new(message), Some(self.self_pid.clone()), None);
+        ContextNext::new(&self.props.context_decorators).request(self, target, envelope)
+    }
+
+    /// Sends response to the sender of the message that is currently being processed.
+    pub fn respond<M>(&self, message: M)
+    where
+        M: Message + Send + Sync + 'static,
+    {
+        let envelope = MessageEnvelope::wrap(AnyMessage::new(message));
+        ContextNext::new(&self.props.context_decorators).respond(self, envelope)
+    }
+
+    /// Spawns child actor with generated name.
+    pub fn spawn(&mut self, props: Props) -> Pid {
+        let id = self.system.registry().next_id();
+        self.spawn_named(props, id)
+            .expect("generated process ids are unique")
+    }
+
+    /// Spawns child actor, it will be stopped when this actor stops.
+    pub fn spawn_named<N>(&mut self, props: Props, name: N) -> Result<Pid, SpawnError>
+    where
+        N: Into<String>,
+    {
+        let name = format!("{}/{}", self.self_pid.id, name.into());
+        if self.props.context_decorators.is_empty() {
+            return self.default_spawn(props, name);
+        }
+        let decorators = self.props.context_decorators.clone();
+        ContextNext::new(&decorators).spawn(self, props, name)
+    }
+
+    /// Stops actor immediately, messages waiting in the mailbox are not processed.
+    pub fn stop(&self, pid: &Pid) {
+        ContextNext::new(&self.props.context_decorators).stop(self, pid)
+    }
+
+    /// Stops actor after it processes messages that are already in its mailbox.
+    pub fn poison(&self, pid: &Pid) {
+        self.send(pid, PoisonPill {})
+    }
+
+    /// Registers this actor to receive [Terminated] when `pid` stops.
+    pub fn watch(&self, pid: &Pid) {
+        let watch = Watch {
+            watcher: Some(self.self_pid.clone()),
+        };
+        self.system
+            .get_process(pid)
+            .send_system_message(pid, SystemMessage::Watch(watch));
+    }
+
+    pub fn unwatch(&self, pid: &Pid) {
+        let unwatch = Unwatch {
+            watcher: Some(self.self_pid.clone()),
+        };
+        self.system
+            .get_process(pid)
+            .send_system_message(pid, SystemMessage::Unwatch(unwatch));
+    }
+
+    /// Awaits `future` without blocking the mailbox and runs `continuation` on the actor's own
+    /// turn once the future completes.
+    ///
+    /// While the future is pending the actor keeps processing other messages. Continuation is
+    /// delivered as [SystemMessage::Continuation], so it runs with exclusive access to the actor
+    /// state and with the message that scheduled it restored in the context.
+    ///
+    /// # Examples:
+    /// ```
+    ///  use async_trait::async_trait;
+    ///  use protoactor::actor::Actor;
+    ///  use protoactor::context::{ActorContext, SenderContext};
+    ///  use protoactor::message::{Message, Pid};
+    ///
+    ///  struct Gateway {
+    ///      backend: Pid,
+    ///      pending: usize,
+    ///  }
+    ///
+    ///  struct Lookup(String);
+    ///
+    ///  #[derive(Default)]
+    ///  struct Found(String);
+    ///
+    ///  impl Message for Lookup {
+    ///      type Result = Found;
+    ///  }
+    ///
+    ///  impl Message for Found {
+    ///      type Result = ();
+    ///  }
+    ///
+    ///  #[async_trait]
+    ///  impl Actor for Gateway {
+    ///      async fn receive(&mut self, ctx: &mut ActorContext) {
+    ///          if let Some(lookup) = ctx.get_message().downcast_ref::<Lookup>() {
+    ///              self.pending += 1;
+    ///              let response = ctx.request_future(&self.backend, Lookup(lookup.0.clone()));
+    ///              ctx.reenter_after(response, |actor: &mut Gateway, ctx, result| {
+    ///                  actor.pending -= 1;
+    ///                  ctx.respond(result.unwrap_or_default());
+    ///              });
+    ///          }
+    ///      }
+    ///  }
+    /// ```
+    pub fn reenter_after<A, F, C>(&self, future: F, continuation: C)
+    where
+        A: Actor,
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+        C: FnOnce(&mut A, &mut ActorContext, F::Output) + Send + 'static,
+    {
+        let message = self.message.clone();
+        #[cfg(feature = "tracing")]
+        let trace = self.trace.clone();
+        let process = self.system.get_process(&self.self_pid);
+        let pid = self.self_pid.clone();
+        tokio::spawn(async move {
+            let output = future.await;
+            #[allow(unused_mut)]
+            let mut continuation = Continuation::new(message, move |actor, ctx| {
+                let actor: &mut dyn Any = actor;
+                match actor.downcast_mut::<A>() {
+                    Some(actor) => continuation(actor, ctx, output),
+                    None => warn!(
+                        "Continuation for {} dropped, actor is not {}",
+                        ctx.self_pid,
+                        type_name::<A>()
+                    ),
+                }
+            });
+            #[cfg(feature = "tracing")]
+            {
+                continuation.trace = trace;
+            }
+            process.send_system_message(&pid, SystemMessage::Continuation(continuation));
+        });
+    }
+
+    pub(crate) fn default_get_headers(&self) -> MessageHeader {
+        self.message.get_header().clone()
+    }
+
+    pub(crate) fn default_send(&self, target: &Pid, mut envelope: MessageEnvelope<AnyMessage>) {
+        self.propagate_headers(&mut envelope);
+        #[cfg(feature = "tracing")]
+        if !envelope.get_header().contains_key(TRACEPARENT_HEADER) {
+            let trace = self.trace.as_ref().map(|trace| trace.context);
+            if let Some(context) = trace.or_else(TraceContext::current) {
+                context.inject_into(&mut envelope);
+            }
+        }
+        let middleware = &self.props.sender_middleware;
+        self.sends.send(
+            middleware,
+            &self.system,
+            Some(&self.self_pid),
+            target,
+            envelope,
+        )
+    }
+
+    /// Copies configured headers of the message that is being processed to outgoing envelope.
+    fn propagate_headers(&self, envelope: &mut MessageEnvelope<AnyMessage>) {
+        let incoming = self.message.get_header();
+        let keys = &self.system.config().propagated_headers;
+        if incoming.is_empty() || keys.is_empty() {
+            return;
+        }
+        let mut header: MessageHeader = keys
+            .iter()
+            .filter_map(|key| Some((key.clone(), incoming.get(key)?.clone())))
+            .collect();
+        header.extend(envelope.get_header().clone());
+        envelope.with_header(header);
+    }
+
+    pub(crate) fn default_respond(&self, envelope: MessageEnvelope<AnyMessage>) {
+        match self.get_sender() {
+            Some(sender) => self.default_send(sender, envelope),
+            None => self
+                .system
+                .dead_letter()
+                .send_user_message(&self.self_pid, envelope),
+        }
+    }
+
+    pub(crate) fn default_spawn(&mut self, props: Props, name: String) -> Result<Pid, SpawnError> {
+        let pid = middleware::spawn(
+            &self.props.spawn_middleware,
+            &self.system,
+            name,
+            &props,
+            Some(&self.self_pid),
+        )?;
+        self.children.insert(pid.clone());
+        Ok(pid)
+    }
+
+    pub(crate) fn default_stop(&self, pid: &Pid) {
+        self.system.get_process(pid).stop(pid)
+    }
+
+    /// Processes mailbox until the actor stops, then sends messages left in it to dead letters.
+    pub(crate) async fn run(mut self, mut mailbox: MailboxReceiver) {
+        while let Some(message) = mailbox.recv().await {
+            let stopped = match message {
+                MailboxMessage::System(message) => self.handle_system_message(message).await,
+                MailboxMessage::User(envelope) => {
+                    if let Some(metrics) = self.system.metrics() {
+                        metrics.mailbox_length(self.props.get_actor_type(), mailbox.len());
+                    }
+                    self.handle_user_message(envelope).await
+                }
+            };
+            if stopped {
+                break;
+            }
+        }
+        let dead_letter = self.system.dead_letter();
+        for message in mailbox.close() {
+            match message {
+                MailboxMessage::System(message) => {
+                    dead_letter.send_system_message(&self.self_pid, message)
+                }
+                MailboxMessage::User(envelope) => {
+                    dead_letter.send_user_message(&self.self_pid, envelope)
+                }
+            }
+        }
+    }
+
+    /// Returns `true` once the actor is stopped.
+    async fn handle_system_message(&mut self, message: SystemMessage) -> bool {
+        match message {
+            SystemMessage::Started => {
+                self.invoke(MessageEnvelope::wrap(AnyMessage::new(Started)))
+                    .await
+            }
+            SystemMessage::Stop => {
+                self.handle_stop().await;
+                return true;
+            }
+            SystemMessage::Watch(Watch {
+                watcher: Some(watcher),
+            }) => {
+                if self.stopping {
+                    self.notify_terminated(&watcher);
+                } else {
+                    self.watchers.insert(watcher);
+                }
+            }
+            SystemMessage::Unwatch(Unwatch {
+                watcher: Some(watcher),
+            }) => {
+                self.watchers.remove(&watcher);
+            }
+            SystemMessage::Watch(_) | SystemMessage::Unwatch(_) => {}
+            SystemMessage::Terminated(terminated) => {
+                if let Some(who) = &terminated.who {
+                    self.children.remove(who);
+                }
+                self.invoke(MessageEnvelope::wrap(AnyMessage::new(terminated)))
+                    .await
+            }
+            SystemMessage::Diagnostics(reply) => {
+                let state = self
+                    .actor
+                    .as_ref()
+                    .and_then(|actor| actor.diagnostics())
+                    .map(|diagnostics| diagnostics.get_diagnostics());
+                let mut children = self.children.iter().cloned().collect::<Vec<_>>();
+                children.sort_by(|left, right| left.id.cmp(&right.id));
+                let _ = reply.send(ActorDiagnostics { state, children });
+            }
+            SystemMessage::Continuation(continuation) => {
+                #[cfg(feature = "tracing")]
+                let trace = continuation.trace.clone();
+                let (message, f) = continuation.into_parts();
+                self.message = message;
+                let mut actor = self.actor.take().expect("actor is not running");
+                #[cfg(feature = "tracing")]
+                self.with_trace(trace, |ctx| f(actor.as_mut(), ctx));
+                #[cfg(not(feature = "tracing"))]
+                f(actor.as_mut(), self);
+                self.actor = Some(actor);
+            }
+        }
+        false
+    }
+
+    /// Returns `true` once the actor is stopped.
+    async fn handle_user_message(&mut self, envelope: MessageEnvelope<AnyMessage>) -> bool {
+        if envelope.get_message().is::<PoisonPill>() {
+            self.handle_stop().await;
+            return true;
+        }
+        if self.system.metrics().is_none() {
+            self.invoke_user(envelope).await;
+            return false;
+        }
+        let message_type = envelope.get_message().get_type_name();
+        let started = Instant::now();
+        self.invoke_user(envelope).await;
+        if let Some(metrics) = self.system.metrics() {
+            metrics.message_received(self.props.get_actor_type(), message_type, started.elapsed());
+        }
+        false
+    }
+
+    /// Invokes the actor with a user message, inside a receive span with the `tracing` feature.
+    async fn invoke_user(&mut self, envelope: MessageEnvelope<AnyMessage>) {
+        #[cfg(feature = "tracing")]
+        {
+            let trace = ReceiveTrace::start(self, &envelope);
+            self.trace = Some(trace.clone());
+            trace.instrument(self.invoke(envelope)).await;
+            self.trace = None;
+        }
+        #[cfg(not(feature = "tracing"))]
+        self.invoke(envelope).await
+    }
+
+    /// Runs `f` with the receive span restored, so messages it sends continue the trace.
+    #[cfg(feature = "tracing")]
+    fn with_trace(&mut self, trace: Option<ReceiveTrace>, f: impl FnOnce(&mut Self)) {
+        let Some(trace) = trace else {
+            return f(self);
+        };
+        self.trace = Some(trace.clone());
+        trace.in_scope(|| f(self));
+        self.trace = None;
+    }
+
+    async fn invoke(&mut self, envelope: MessageEnvelope<AnyMessage>) {
+        if self.props.receiver_middleware.is_empty() {
+            return self.receive_envelope(envelope).await;
+        }
+        let middleware = self.props.receiver_middleware.clone();
+        ReceiverNext::new(&middleware).run(self, envelope).await
+    }
+
+    /// Passes envelope to the actor, last stage of the receiver pipeline.
+    pub(crate) async fn receive_envelope(&mut self, envelope: MessageEnvelope<AnyMessage>) {
+        self.message = envelope;
+        let mut actor = self.actor.take().expect("actor is not running");
+        actor.receive(self).await;
+        self.actor = Some(actor);
+    }
+
+    async fn handle_stop(&mut self) {
+        self.stopping = true;
+        self.invoke(MessageEnvelope::wrap(AnyMessage::new(Stopping)))
+            .await;
+        for child in self.children.iter() {
+            self.default_stop(child);
+        }
+        self.system.registry().remove(&self.self_pid);
+        self.invoke(MessageEnvelope::wrap(AnyMessage::new(Stopped)))
+            .await;
+
+        if let Some(metrics) = self.system.metrics() {
+            metrics.actor_stopped(self.props.get_actor_type());
+        }
+
+        let watchers: HashSet<_> = self.watchers.drain().chain(self.parent.clone()).collect();
+        for watcher in watchers.iter() {
+            self.notify_terminated(watcher);
+        }
+    }
+
+    fn notify_terminated(&self, watcher: &Pid) {
+        let terminated = Terminated {
+            who: Some(self.self_pid.clone()),
+            why: TerminatedReason::Stopped as i32,
+        };
+        self.system
+            .get_process(watcher)
+            .send_system_message(watcher, SystemMessage::Terminated(terminated));
+    }
+}
+
+impl SenderContext for ActorContext {
+    #[inline]
+    fn get_system(&self) -> &Arc<ActorSystem> {
+        &self.system
+    }
+
+    fn get_headers(&self) -> MessageHeader {
+        ContextNext::new(&self.props.context_decorators).get_headers(self)
+    }
+
+    fn send_envelope(&self, target: &Pid, envelope: MessageEnvelope<AnyMessage>) {
+        ContextNext::new(&self.props.context_decorators).send(self, target, envelope)
+    }
+
+    fn request_future<M>(&self, target: &Pid, message: M) -> RequestFuture<M::Result>
+    where
+        M: Message + Send + Sync + 'static,
+        M::Result: Send + Sync + 'static,
+    {
+        let timeout = self.system.config().actor_request_timeout;
+        let (future_pid, future) = FutureProcess::spawn(&self.system, timeout);
+        let envelope = MessageEnvelope::new(AnyMessage::new(message), Some(future_pid), None);
+        ContextNext::new(&self.props.context_decorators).request_future(self, target, envelope);
+        future
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actor::{Actor, Props};
+    use crate::context::{ActorContext, SenderContext};
+    use crate::message::{Message, MessageHeader, Pid, Started, Terminated, TerminatedReason};
+    use crate::process::RequestError;
+    use crate::system::config::ActorSystemConfig;
+    use crate::system::ActorSystem;
+    use async_trait::async_trait;
+    use std::sync::Mutex;
+    use std::time::Duration;
+    use tokio::sync::oneshot;
+
+    struct Ping;
+
+    struct Pong(usize);
+
+    struct Slow(Mutex<Option<oneshot::Receiver<usize>>>);
+
+    struct Count;
+
+    impl Message for Ping {
+        type Result = Pong;
+    }
+
+    impl Message for Pong {
+        type Result = ();
+    }
+
+    impl Message for Slow {
+        type Result = Pong;
+    }
+
+    impl Message for Count {
+        type Result = Pong;
+    }
+
+    #[derive(Default)]
+    struct TestActor {
+        received: usize,
+    }
+
+    #[async_trait]
+    impl Actor for TestActor {
+        async fn receive(&mut self, ctx: &mut ActorContext) {
+            let message = ctx.get_message();
+            if message.is::<Ping>() {
+                self.received += 1;
+                ctx.respond(Pong(self.received));
+            } else if message.is::<Count>() {
+                ctx.respond(Pong(self.received));
+            } else if let Some(slow) = message.downcast_ref::<Slow>() {
+                let value = slow.0.lock().unwrap().take().unwrap();
+                ctx.reenter_after(value, |actor: &mut TestActor, ctx, value| {
+                    actor.received += value.unwrap();
+                    ctx.respond(Pong(actor.received));
+                });
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn should_respond_to_request() {
+        let system = ActorSystem::new();
+        let root = system.root();
+        let pid = root.spawn(Props::from_producer(TestActor::default));
+
+        let pong = root.request_future(&pid, Ping).await.unwrap();
+
+        assert_eq!(1, pong.0);
+    }
+
+    #[tokio::test]
+    async fn should_fail_request_to_dead_letter() {
+        let system = ActorSystem::new();
+        let root = system.root();
+        let pid = root.spawn(Props::from_producer(TestActor::default));
+        root.stop(&pid);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+
+        let result = root.request_future(&pid, Ping).await;
+
+        assert_eq!(
+            Err(RequestError::DeadLetter(Some(pid))),
+            result.map(|p| p.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_fail_requests_left_in_mailbox_of_stopped_actor() {
+        let system = ActorSystem::new();
+        let root = system.root();
+        let pid = root.spawn(Props::from_producer(TestActor::default));
+        root.poison(&pid);
+
+        let result = tokio::time::timeout(Duration::from_secs(1), root.request_future(&pid, Ping))
+            .await
+            .unwrap();
+
+        assert_eq!(
+            Err(RequestError::DeadLetter(Some(pid))),
+            result.map(|p| p.0)
+        );
+    }
+
+    #[tokio::test]
+    async fn should_process_messages_while_reentrant_future_is_pending() {
+        let system = ActorSystem::new();
+        let root = system.root();
+        let pid = root.spawn(Props::from_producer(TestActor::default));
+        let (tx, rx) = oneshot::channel();
+
+        let slow = root.request_future(&pid, Slow(Mutex::new(Some(rx))));
+        assert_eq!(1, root.request_future(&pid, Ping).await.unwrap().0);
+        assert_eq!(2, root.request_future(&pid, Ping).await.unwrap().0);
+        tx.send(10).unwrap();
+
+        // continuation responds to the sender of the message that scheduled it
+        assert_eq!(12, slow.await.unwrap().0);
+        assert_eq!(12, root.request_future(&pid, Count).await.unwrap().0);
+    }
+
+    struct Headers;
+
+    struct HeaderReply(MessageHeader);
+
+    struct Forward(Pid);
+
+    impl Message for Headers {
+        type Result = HeaderReply;
+    }
+
+    impl Message for HeaderReply {
+        type Result = ();
+    }
+
+    impl Message for Forward {
+        type Result = HeaderReply;
+    }
+
+    struct HeaderActor;
+
+    #[async_trait]
+    impl Actor for HeaderActor {
+        async fn receive(&mut self, ctx: &mut ActorContext) {
+            let message = ctx.get_message();
+            if message.is::<Headers>() {
+                ctx.respond(HeaderReply(ctx.get_headers()));
+            } else if let Some(Forward(target)) = message.downcast_ref::<Forward>() {
+                let response = ctx.request_future(target, Headers);
+                ctx.reenter_after(response, |_: &mut HeaderActor, ctx, reply| {
+                    ctx.respond(reply.unwrap())
+                });
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn should_propagate_configured_headers() {
+        let config = ActorSystemConfig::setup().with_propagated_headers(["tenant", "correlation"]);
+        let system = ActorSystem::with_config(config);
+        let target = system.root().spawn(Props::from_producer(|| HeaderActor));
+        let forwarder = system.root().spawn(Props::from_producer(|| HeaderActor));
+        let root = system.root().with_headers([
+            ("tenant".to_string(), "acme".to_string()),
+            ("correlation".to_string(), "42".to_string()),
+            ("secret".to_string(), "token".to_string()),
+        ]);
+
+        let mut reply = root
+            .request_future(&forwarder, Forward(target))
+            .await
+            .unwrap();
+
+        reply.0.remove("traceparent");
+        assert_eq!(
+            MessageHeader::from([
+                ("tenant".to_string(), "acme".to_string()),
+                ("correlation".to_string(), "42".to_string()),
+            ]),
+            reply.0
+        );
+    }
+
+    struct Watcher(Option<oneshot::Sender<Terminated>>);
+
+    #[async_trait]
+    impl Actor for Watcher {
+        async fn receive(&mut self, ctx: &mut ActorContext) {
+            let message = ctx.get_message();
+            if message.is::<Started>() {
+                let child = ctx.spawn(Props::from_producer(TestActor::default));
+                ctx.watch(&child);
+                ctx.stop(&child);
+            } else if let Some(terminated) = message.downcast_ref::<Terminated>() {
+                if let Some(tx) = self.0.take() {
+                    let _ = tx.send(terminated.clone());
+                }
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn should_notify_watchers_when_actor_stops() {
+        let system = ActorSystem::new();
+        let (tx, rx) = oneshot::channel();
+        let tx = std::sync::Mutex::new(Some(tx));
+        system.root().spawn(Props::from_producer(move || {
+            Watcher(tx.lock().unwrap().take())
+        }));
+
+        let terminated = rx.await.unwrap();
+
+        assert_eq!(TerminatedReason::Stopped as i32, terminated.why);
+        assert!(system.registry().get(&terminated.who.unwrap()).is_none());
+    }
 }
@@ -0,0 +1,39 @@
+use crate::actor::Actor;
+use crate::context::ActorContext;
+use crate::message::{AnyMessage, MessageEnvelope};
+#[cfg(feature = "tracing")]
+use crate::trace::ReceiveTrace;
+
+type ContinuationFn = Box<dyn FnOnce(&mut dyn Actor, &mut ActorContext) + Send>;
+
+/// Work scheduled with [ActorContext::reenter_after] that resumes once the awaited future
+/// completes.
+///
+/// Continuation carries envelope of the message that was being processed when it was scheduled,
+/// so during continuation [ActorContext::get_message] and [ActorContext::respond] behave as if
+/// that message was still being processed.
+pub struct Continuation {
+    message: MessageEnvelope<AnyMessage>,
+    /// Receive span of the message, restored while the continuation runs.
+    #[cfg(feature = "tracing")]
+    pub(super) trace: Option<ReceiveTrace>,
+    f: ContinuationFn,
+}
+
+impl Continuation {
+    pub(crate) fn new<F>(message: MessageEnvelope<AnyMessage>, f: F) -> Self
+    where
+        F: FnOnce(&mut dyn Actor, &mut ActorContext) + Send + 'static,
+    {
+        Self {
+            message,
+            #[cfg(feature = "tracing")]
+            trace: None,
+            f: Box::new(f),
+        }
+    }
+
+    pub(crate) fn into_parts(self) -> (MessageEnvelope<AnyMessage>, ContinuationFn) {
+        (self.message, self.f)
+    }
+}
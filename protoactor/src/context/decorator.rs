@@ -0,0 +1,269 @@
+use crate::actor::Props;
+use crate::context::ActorContext;
+use crate::message::{AnyMessage, MessageEnvelope, MessageHeader, Pid};
+use crate::process::SpawnError;
+use std::sync::Arc;
+
+/// Wraps operations of the [ActorContext].
+///
+/// Unlike [middleware](crate::middleware), which only sees envelopes on their way in or out of
+/// the actor, decorator intercepts what the actor does with its context. Every method defaults
+/// to calling the next decorator, so implementation overrides only operations it is interested
+/// in. Decorators are attached with [Props::with_context_decorator] and run in the order they
+/// were added.
+pub trait ContextDecorator
+where
+    Self: Send + Sync + 'static,
+{
+    /// Wraps [SenderContext::get_headers](crate::context::SenderContext::get_headers).
+    fn get_headers(&self, ctx: &ActorContext, next: ContextNext<'_>) -> MessageHeader {
+        next.get_headers(ctx)
+    }
+
+    /// Wraps [SenderContext::send](crate::context::SenderContext::send).
+    fn send(
+        &self,
+        ctx: &ActorContext,
+        target: &Pid,
+        envelope: MessageEnvelope<AnyMessage>,
+        next: ContextNext<'_>,
+    ) {
+        next.send(ctx, target, envelope)
+    }
+
+    /// Wraps [ActorContext::request], envelope sender is the actor itself.
+    fn request(
+        &self,
+        ctx: &ActorContext,
+        target: &Pid,
+        envelope: MessageEnvelope<AnyMessage>,
+        next: ContextNext<'_>,
+    ) {
+        next.request(ctx, target, envelope)
+    }
+
+    /// Wraps [SenderContext::request_future](crate::context::SenderContext::request_future),
+    /// envelope sender is the future awaiting the response.
+    fn request_future(
+        &self,
+        ctx: &ActorContext,
+        target: &Pid,
+        envelope: MessageEnvelope<AnyMessage>,
+        next: ContextNext<'_>,
+    ) {
+        next.request_future(ctx, target, envelope)
+    }
+
+    /// Wraps [ActorContext::respond].
+    fn respond(
+        &self,
+        ctx: &ActorContext,
+        envelope: MessageEnvelope<AnyMessage>,
+        next: ContextNext<'_>,
+    ) {
+        next.respond(ctx, envelope)
+    }
+
+    /// Wraps [ActorContext::spawn_named], `name` is the full id of the child.
+    fn spawn(
+        &self,
+        ctx: &mut ActorContext,
+        props: Props,
+        name: String,
+        next: ContextNext<'_>,
+    ) -> Result<Pid, SpawnError> {
+        next.spawn(ctx, props, name)
+    }
+
+    /// Wraps [ActorContext::stop].
+    fn stop(&self, ctx: &ActorContext, pid: &Pid, next: ContextNext<'_>) {
+        next.stop(ctx, pid)
+    }
+}
+
+/// Rest of the decorator chain, ends with the default [ActorContext] behaviour.
+pub struct ContextNext<'a> {
+    decorators: &'a [Arc<dyn ContextDecorator>],
+}
+
+impl<'a> ContextNext<'a> {
+    pub(crate) fn new(decorators: &'a [Arc<dyn ContextDecorator>]) -> Self {
+        Self { decorators }
+    }
+
+    fn split(&self) -> Option<(&'a Arc<dyn ContextDecorator>, ContextNext<'a>)> {
+        self.decorators
+            .split_first()
+            .map(|(decorator, rest)| (decorator, ContextNext::new(rest)))
+    }
+
+    pub fn get_headers(self, ctx: &ActorContext) -> MessageHeader {
+        match self.split() {
+            Some((decorator, next)) => decorator.get_headers(ctx, next),
+            None => ctx.default_get_headers(),
+        }
+    }
+
+    pub fn send(self, ctx: &ActorContext, target: &Pid, envelope: MessageEnvelope<AnyMessage>) {
+        match self.split() {
+            Some((decorator, next)) => decorator.send(ctx, target, envelope, next),
+            None => ctx.default_send(target, envelope),
+        }
+    }
+
+    pub fn request(self, ctx: &ActorContext, target: &Pid, envelope: MessageEnvelope<AnyMessage>) {
+        match self.split() {
+            Some((decorator, next)) => decorator.request(ctx, target, envelope, next),
+            None => ctx.default_send(target, envelope),
+        }
+    }
+
+    pub fn request_future(
+        self,
+        ctx: &ActorContext,
+        target: &Pid,
+        envelope: MessageEnvelope<AnyMessage>,
+    ) {
+        match self.split() {
+            Some((decorator, next)) => decorator.request_future(ctx, target, envelope, next),
+            None => ctx.default_send(target, envelope),
+        }
+    }
+
+    pub fn respond(self, ctx: &ActorContext, envelope: MessageEnvelope<AnyMessage>) {
+        match self.split() {
+            Some((decorator, next)) => decorator.respond(ctx, envelope, next),
+            None => ctx.default_respond(envelope),
+        }
+    }
+
+    pub fn spawn(
+        self,
+        ctx: &mut ActorContext,
+        props: Props,
+        name: String,
+    ) -> Result<Pid, SpawnError> {
+        match self.split() {
+            Some((decorator, next)) => decorator.spawn(ctx, props, name, next),
+            None => ctx.default_spawn(props, name),
+        }
+    }
+
+    pub fn stop(self, ctx: &ActorContext, pid: &Pid) {
+        match self.split() {
+            Some((decorator, next)) => decorator.stop(ctx, pid, next),
+            None => ctx.default_stop(pid),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actor::{Actor, Props};
+    use crate::context::{ActorContext, ContextDecorator, ContextNext, SenderContext};
+    use crate::message::{AnyMessage, Message, MessageEnvelope, MessageHeader, Pid, Started};
+    use crate::process::SpawnError;
+    use crate::system::ActorSystem;
+    use async_trait::async_trait;
+    use std::sync::{Arc, Mutex};
+
+    struct Ping;
+
+    struct Pong(Option<String>);
+
+    impl Message for Ping {
+        type Result = Pong;
+    }
+
+    impl Message for Pong {
+        type Result = ();
+    }
+
+    /// Child of [PingActor] that does nothing.
+    struct LeafActor;
+
+    #[async_trait]
+    impl Actor for LeafActor {
+        async fn receive(&mut self, _ctx: &mut ActorContext) {}
+    }
+
+    struct PingActor;
+
+    #[async_trait]
+    impl Actor for PingActor {
+        async fn receive(&mut self, ctx: &mut ActorContext) {
+            let message = ctx.get_message();
+            if message.is::<Started>() {
+                let child = ctx.spawn(Props::from_producer(|| LeafActor));
+                ctx.stop(&child);
+            } else if message.is::<Ping>() {
+                ctx.respond(Pong(ctx.get_headers().get("tenant").cloned()));
+            }
+        }
+    }
+
+    #[derive(Default)]
+    struct RecordOperations(Mutex<Vec<String>>);
+
+    impl ContextDecorator for Arc<RecordOperations> {
+        fn respond(
+            &self,
+            ctx: &ActorContext,
+            envelope: MessageEnvelope<AnyMessage>,
+            next: ContextNext<'_>,
+        ) {
+            let operation = format!("respond {}", envelope.get_message().type_name());
+            self.0.lock().unwrap().push(operation);
+            next.respond(ctx, envelope)
+        }
+
+        fn spawn(
+            &self,
+            ctx: &mut ActorContext,
+            props: Props,
+            name: String,
+            next: ContextNext<'_>,
+        ) -> Result<Pid, SpawnError> {
+            self.0.lock().unwrap().push("spawn".to_string());
+            next.spawn(ctx, props, name)
+        }
+
+        fn stop(&self, ctx: &ActorContext, pid: &Pid, next: ContextNext<'_>) {
+            self.0.lock().unwrap().push("stop".to_string());
+            next.stop(ctx, pid)
+        }
+    }
+
+    struct InjectTenant;
+
+    impl ContextDecorator for InjectTenant {
+        fn get_headers(&self, ctx: &ActorContext, next: ContextNext<'_>) -> MessageHeader {
+            let mut headers = next.get_headers(ctx);
+            headers.insert("tenant".to_string(), "acme".to_string());
+            headers
+        }
+    }
+
+    #[tokio::test]
+    async fn should_intercept_context_operations() {
+        let system = ActorSystem::new();
+        let root = system.root();
+        let operations = Arc::new(RecordOperations::default());
+        let props = Props::from_producer(|| PingActor)
+            .with_context_decorator(operations.clone())
+            .with_context_decorator(InjectTenant);
+        let pid = root.spawn(props);
+
+        let pong = root.request_future(&pid, Ping).await.unwrap();
+
+        assert_eq!(Some("acme".to_string()), pong.0);
+        assert_eq!(
+            vec![
+                "spawn".to_string(),
+                "stop".to_string(),
+                "respond protoactor::context::decorator::tests::Pong".to_string()
+            ],
+            *operations.0.lock().unwrap()
+        );
+    }
+}
@@ -0,0 +1,112 @@
+use crate::actor::Props;
+use crate::context::SenderContext;
+use crate::message::{AnyMessage, MessageEnvelope, MessageHeader, Pid, PoisonPill};
+use crate::middleware::{self, SendQueue, SenderMiddleware, SpawnMiddleware};
+use crate::process::SpawnError;
+use crate::system::ActorSystem;
+#[cfg(feature = "tracing")]
+use crate::trace::{TraceContext, TRACEPARENT_HEADER};
+use std::sync::Arc;
+
+/// Context used to spawn and communicate with actors from outside of the actor system.
+#[derive(Clone)]
+pub struct RootContext {
+    system: Arc<ActorSystem>,
+    headers: MessageHeader,
+    sender_middleware: Vec<Arc<dyn SenderMiddleware>>,
+    spawn_middleware: Vec<Arc<dyn SpawnMiddleware>>,
+    sends: SendQueue,
+}
+
+impl RootContext {
+    pub(crate) fn new(system: Arc<ActorSystem>) -> Self {
+        Self {
+            system,
+            headers: Default::default(),
+            sender_middleware: Vec::new(),
+            spawn_middleware: Vec::new(),
+            sends: SendQueue::default(),
+        }
+    }
+
+    /// Headers added to every message sent from this context.
+    pub fn with_headers<T>(self, headers: T) -> Self
+    where
+        T: Into<MessageHeader>,
+    {
+        Self {
+            headers: headers.into(),
+            ..self
+        }
+    }
+
+    /// Adds middleware that wraps every message sent from this context.
+    pub fn with_sender_middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: SenderMiddleware,
+    {
+        self.sender_middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Adds middleware that wraps every actor spawned from this context.
+    pub fn with_spawn_middleware<M>(mut self, middleware: M) -> Self
+    where
+        M: SpawnMiddleware,
+    {
+        self.spawn_middleware.push(Arc::new(middleware));
+        self
+    }
+
+    /// Spawns top level actor with generated name.
+    pub fn spawn(&self, props: Props) -> Pid {
+        let id = self.system.registry().next_id();
+        self.spawn_named(props, id)
+            .expect("generated process ids are unique")
+    }
+
+    /// Spawns top level actor with the given name.
+    pub fn spawn_named<N>(&self, props: Props, name: N) -> Result<Pid, SpawnError>
+    where
+        N: Into<String>,
+    {
+        let middleware = &self.spawn_middleware;
+        middleware::spawn(middleware, &self.system, name.into(), &props, None)
+    }
+
+    /// Stops actor immediately, messages waiting in the mailbox are not processed.
+    pub fn stop(&self, pid: &Pid) {
+        self.system.get_process(pid).stop(pid)
+    }
+
+    /// Stops actor after it processes messages that are already in its mailbox.
+    pub fn poison(&self, pid: &Pid) {
+        self.send(pid, PoisonPill {})
+    }
+}
+
+impl SenderContext for RootContext {
+    #[inline]
+    fn get_system(&self) -> &Arc<ActorSystem> {
+        &self.system
+    }
+
+    fn get_headers(&self) -> MessageHeader {
+        self.headers.clone()
+    }
+
+    fn send_envelope(&self, target: &Pid, mut envelope: MessageEnvelope<AnyMessage>) {
+        if !self.headers.is_empty() {
+            envelope.merge_header(self.headers.clone());
+        }
+        #[cfg(feature = "tracing")]
+        if !envelope.get_header().contains_key(TRACEPARENT_HEADER) {
+            if let Some(context) = TraceContext::current() {
+                context.inject_into(&mut envelope);
+            }
+        }
+        let middleware = &self.sender_middleware;
+        self.sends
+            .send(middleware, &self.system, None, target, envelope)
+    }
+}
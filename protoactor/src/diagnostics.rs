@@ -1,3 +1,203 @@
+//! Runtime diagnostics of the actor system.
+//!
+//! [ActorSystem::diagnostics_snapshot](crate::system::ActorSystem::diagnostics_snapshot) lists
+//! every registered process without waiting for any of them, while
+//! [ActorSystem::diagnostics](crate::system::ActorSystem::diagnostics) also asks the actor for
+//! its own state summary if it implements [Diagnostics].
+use crate::message::Pid;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
 pub trait DiagnosticsTypeName {
     fn get_type_name(&self) -> String;
 }
+
+/// Structured summary of the actor state, field name to value.
+pub type DiagnosticsState = BTreeMap<String, String>;
+
+/// Implemented by actors that report their state for diagnostics.
+///
+/// Actor opts in by returning itself from [Actor::diagnostics](crate::actor::Actor::diagnostics).
+/// State is collected by the actor on its own turn, between messages.
+pub trait Diagnostics {
+    fn get_diagnostics(&self) -> DiagnosticsState;
+}
+
+/// What an actor reports about itself on
+/// [SystemMessage::Diagnostics](crate::message::SystemMessage::Diagnostics).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ActorDiagnostics {
+    pub state: Option<DiagnosticsState>,
+    /// Children ordered by process id.
+    pub children: Vec<Pid>,
+}
+
+/// What the actor system knows about a single registered process.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessDiagnostics {
+    pub pid: Pid,
+    /// Actor type, or type of the process for processes that are not actors.
+    pub type_name: String,
+    /// Number of user messages waiting in the mailbox, `None` for processes without one.
+    pub mailbox_length: Option<usize>,
+    pub parent: Option<Pid>,
+    pub children: Vec<Pid>,
+    /// State reported by the actor, see [Diagnostics].
+    pub state: Option<DiagnosticsState>,
+}
+
+impl ProcessDiagnostics {
+    pub fn new(pid: Pid, type_name: impl Into<String>) -> Self {
+        Self {
+            pid,
+            type_name: type_name.into(),
+            mailbox_length: None,
+            parent: None,
+            children: Vec::new(),
+            state: None,
+        }
+    }
+}
+
+impl Display for ProcessDiagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.pid, self.type_name)?;
+        if let Some(length) = self.mailbox_length {
+            write!(f, " mailbox={}", length)?;
+        }
+        if let Some(parent) = &self.parent {
+            write!(f, " parent={}", parent)?;
+        }
+        if !self.children.is_empty() {
+            let children = self.children.iter().map(Pid::to_string);
+            write!(f, " children=[{}]", children.collect::<Vec<_>>().join(", "))?;
+        }
+        if let Some(state) = &self.state {
+            let fields = state
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value));
+            write!(f, " state={{{}}}", fields.collect::<Vec<_>>().join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Every process registered in the actor system at a point in time, ordered by process id.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SystemDiagnostics {
+    pub address: String,
+    pub processes: Vec<ProcessDiagnostics>,
+}
+
+impl SystemDiagnostics {
+    pub fn get(&self, pid: &Pid) -> Option<&ProcessDiagnostics> {
+        self.processes.iter().find(|process| &process.pid == pid)
+    }
+}
+
+impl Display for SystemDiagnostics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{} ({} processes)", self.address, self.processes.len())?;
+        for process in &self.processes {
+            writeln!(f, "  {}", process)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actor::{Actor, Props};
+    use crate::context::{ActorContext, SenderContext};
+    use crate::diagnostics::{Diagnostics, DiagnosticsState};
+    use crate::message::{Message, Pid, Started};
+    use crate::system::ActorSystem;
+    use async_trait::async_trait;
+
+    struct Count;
+
+    struct Counted(usize);
+
+    impl Message for Count {
+        type Result = Counted;
+    }
+
+    impl Message for Counted {
+        type Result = ();
+    }
+
+    struct ParentActor {
+        received: usize,
+    }
+
+    #[async_trait]
+    impl Actor for ParentActor {
+        async fn receive(&mut self, ctx: &mut ActorContext) {
+            let message = ctx.get_message();
+            if message.is::<Started>() {
+                ctx.spawn_named(Props::from_producer(|| ChildActor), "child")
+                    .unwrap();
+            } else if message.is::<Count>() {
+                self.received += 1;
+                ctx.respond(Counted(self.received));
+            }
+        }
+
+        fn diagnostics(&self) -> Option<&dyn Diagnostics> {
+            Some(self)
+        }
+    }
+
+    impl Diagnostics for ParentActor {
+        fn get_diagnostics(&self) -> DiagnosticsState {
+            [("received".to_string(), self.received.to_string())].into()
+        }
+    }
+
+    struct ChildActor;
+
+    #[async_trait]
+    impl Actor for ChildActor {
+        async fn receive(&mut self, _: &mut ActorContext) {}
+    }
+
+    #[tokio::test]
+    async fn should_report_actor_state_and_hierarchy() {
+        let system = ActorSystem::new();
+        let root = system.root();
+        let parent = root
+            .spawn_named(
+                Props::from_producer(|| ParentActor { received: 0 }),
+                "parent",
+            )
+            .unwrap();
+        let counted = root.request_future(&parent, Count).await.unwrap();
+        let child = Pid::new("nohost", "parent/child");
+
+        let diagnostics = system.diagnostics(&parent).await.unwrap();
+        let snapshot = system.diagnostics_snapshot();
+
+        assert_eq!(1, counted.0);
+        assert_eq!(
+            "protoactor::diagnostics::tests::ParentActor",
+            diagnostics.type_name
+        );
+        assert_eq!(Some(0), diagnostics.mailbox_length);
+        assert_eq!(vec![child.clone()], diagnostics.children);
+        assert_eq!(
+            Some([("received".to_string(), "1".to_string())].into()),
+            diagnostics.state
+        );
+        let child = snapshot.get(&child).unwrap();
+        assert_eq!(Some(parent.clone()), child.parent);
+        assert_eq!(None, child.state);
+        assert_eq!(
+            None,
+            system.diagnostics(&Pid::new("nohost", "missing")).await
+        );
+        assert_eq!(
+            None,
+            system.diagnostics(&Pid::new("remote", "parent")).await
+        );
+    }
+}
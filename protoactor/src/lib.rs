@@ -2,11 +2,21 @@
 // extern crate log;
 
 pub mod actor;
+#[cfg(feature = "cluster")]
+pub mod cluster;
 pub mod context;
 pub mod diagnostics;
+mod mailbox;
 pub mod message;
+pub mod metrics;
+pub mod middleware;
 pub mod process;
+#[cfg(feature = "remote")]
+pub mod remote;
+pub mod router;
 pub mod system;
+#[cfg(feature = "tracing")]
+pub mod trace;
 
 #[cfg(test)]
 mod tests {
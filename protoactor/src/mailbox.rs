@@ -0,0 +1,106 @@
+use crate::message::{AnyMessage, MessageEnvelope, SystemMessage};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+pub(crate) enum MailboxMessage {
+    System(SystemMessage),
+    User(MessageEnvelope<AnyMessage>),
+}
+
+/// Sending half of the actor mailbox.
+///
+/// System messages are kept in a separate queue and are always dispatched before user messages.
+pub(crate) struct Mailbox {
+    system: UnboundedSender<SystemMessage>,
+    user: UnboundedSender<MessageEnvelope<AnyMessage>>,
+    user_len: Arc<AtomicUsize>,
+}
+
+pub(crate) struct MailboxReceiver {
+    system: UnboundedReceiver<SystemMessage>,
+    user: UnboundedReceiver<MessageEnvelope<AnyMessage>>,
+    user_len: Arc<AtomicUsize>,
+}
+
+pub(crate) fn unbounded() -> (Mailbox, MailboxReceiver) {
+    let (system_tx, system_rx) = unbounded_channel();
+    let (user_tx, user_rx) = unbounded_channel();
+    let user_len = Arc::new(AtomicUsize::new(0));
+    (
+        Mailbox {
+            system: system_tx,
+            user: user_tx,
+            user_len: user_len.clone(),
+        },
+        MailboxReceiver {
+            system: system_rx,
+            user: user_rx,
+            user_len,
+        },
+    )
+}
+
+impl Mailbox {
+    /// Posts message to the mailbox, or returns it back if mailbox is closed.
+    #[allow(clippy::result_large_err)]
+    pub fn post_user_message(
+        &self,
+        envelope: MessageEnvelope<AnyMessage>,
+    ) -> Result<(), MessageEnvelope<AnyMessage>> {
+        self.user_len.fetch_add(1, Ordering::Relaxed);
+        self.user.send(envelope).map_err(|err| {
+            self.user_len.fetch_sub(1, Ordering::Relaxed);
+            err.0
+        })
+    }
+
+    #[allow(clippy::result_large_err)]
+    pub fn post_system_message(&self, message: SystemMessage) -> Result<(), SystemMessage> {
+        self.system.send(message).map_err(|err| err.0)
+    }
+
+    /// Number of user messages waiting to be processed.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.user_len.load(Ordering::Relaxed)
+    }
+}
+
+impl MailboxReceiver {
+    /// Number of user messages waiting to be processed.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.user_len.load(Ordering::Relaxed)
+    }
+
+    /// Closes the mailbox and returns the messages still waiting, later posts fail.
+    pub fn close(&mut self) -> Vec<MailboxMessage> {
+        self.system.close();
+        self.user.close();
+        let mut messages = Vec::new();
+        while let Ok(message) = self.system.try_recv() {
+            messages.push(MailboxMessage::System(message));
+        }
+        while let Ok(envelope) = self.user.try_recv() {
+            self.user_len.fetch_sub(1, Ordering::Relaxed);
+            messages.push(MailboxMessage::User(envelope));
+        }
+        messages
+    }
+
+    /// Waits for the next message, system messages first.
+    ///
+    /// Returns [None] once all senders are dropped and both queues are drained.
+    pub async fn recv(&mut self) -> Option<MailboxMessage> {
+        tokio::select! {
+            biased;
+            Some(message) = self.system.recv() => Some(MailboxMessage::System(message)),
+            Some(envelope) = self.user.recv() => {
+                self.user_len.fetch_sub(1, Ordering::Relaxed);
+                Some(MailboxMessage::User(envelope))
+            },
+            else => None,
+        }
+    }
+}
@@ -1,11 +1,19 @@
+mod any_message;
+mod lifecycle;
 mod message_envelope;
+mod pid;
 mod protos;
 
+pub use any_message::*;
+pub use lifecycle::*;
 pub use message_envelope::*;
 #[doc(inline)]
 pub use protos::*;
 
+use crate::context::Continuation;
+use crate::diagnostics::ActorDiagnostics;
 use std::sync::Arc;
+use tokio::sync::oneshot;
 
 // pub trait IsMessage {}
 
@@ -34,4 +42,13 @@ pub trait MessageResponse {}
 pub enum SystemMessage {
     Started,
     Stop,
+    Watch(Watch),
+    Unwatch(Unwatch),
+    Terminated(Terminated),
+    /// Resumes work scheduled with [ActorContext::reenter_after](crate::context::ActorContext::reenter_after)
+    /// on the actor's own turn.
+    Continuation(Continuation),
+    /// Asks the actor for its [Diagnostics](crate::diagnostics::Diagnostics) state and
+    /// children, processes that are not actors drop the sender.
+    Diagnostics(oneshot::Sender<ActorDiagnostics>),
 }
@@ -0,0 +1,129 @@
+use crate::diagnostics::DiagnosticsTypeName;
+use crate::message::Message;
+use std::any::{type_name, Any, TypeId};
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+/// Type erased message as it travels through mailboxes and processes.
+///
+/// Wrapped value is shared, so cloning [AnyMessage] is cheap and does not require message to
+/// implement [Clone].
+#[derive(Clone)]
+pub struct AnyMessage {
+    inner: Arc<dyn Any + Send + Sync>,
+    type_name: &'static str,
+}
+
+impl AnyMessage {
+    pub fn new<M>(message: M) -> Self
+    where
+        M: Message + Send + Sync + 'static,
+    {
+        Self {
+            inner: Arc::new(message),
+            type_name: type_name::<M>(),
+        }
+    }
+
+    /// Returns `true` if wrapped message is of type `T`.
+    #[inline]
+    pub fn is<T: 'static>(&self) -> bool {
+        self.inner.is::<T>()
+    }
+
+    #[inline]
+    pub fn downcast_ref<T: 'static>(&self) -> Option<&T> {
+        self.inner.downcast_ref::<T>()
+    }
+
+    /// Converts message into shared reference of `T`, or returns self back if message is of
+    /// some other type.
+    pub fn downcast<T>(self) -> Result<Arc<T>, Self>
+    where
+        T: Send + Sync + 'static,
+    {
+        let type_name = self.type_name;
+        self.inner
+            .downcast::<T>()
+            .map_err(|inner| Self { inner, type_name })
+    }
+
+    /// Takes ownership of wrapped message. Fails if message is of some other type or if it is
+    /// still shared with other clones of this [AnyMessage].
+    pub fn try_unwrap<T>(self) -> Result<T, Self>
+    where
+        T: Send + Sync + 'static,
+    {
+        let type_name = self.type_name;
+        self.downcast::<T>().and_then(|arc| {
+            Arc::try_unwrap(arc).map_err(|arc| Self {
+                inner: arc,
+                type_name,
+            })
+        })
+    }
+
+    /// [TypeId] of the wrapped message.
+    #[inline]
+    pub fn message_type_id(&self) -> TypeId {
+        (*self.inner).type_id()
+    }
+
+    /// Name of the wrapped message type.
+    #[inline]
+    pub fn type_name(&self) -> &'static str {
+        self.type_name
+    }
+}
+
+impl Message for AnyMessage {
+    type Result = AnyMessage;
+}
+
+impl DiagnosticsTypeName for AnyMessage {
+    fn get_type_name(&self) -> String {
+        self.type_name.to_string()
+    }
+}
+
+impl Debug for AnyMessage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "AnyMessage({})", self.type_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::{AnyMessage, Message};
+
+    #[derive(Debug, PartialEq)]
+    struct TestMessage(u32);
+
+    impl Message for TestMessage {
+        type Result = ();
+    }
+
+    #[test]
+    fn it_can_downcast_to_wrapped_type() {
+        let message = AnyMessage::new(TestMessage(7));
+
+        assert!(message.is::<TestMessage>());
+        assert!(!message.is::<String>());
+        assert_eq!(Some(&TestMessage(7)), message.downcast_ref::<TestMessage>());
+        assert_eq!(
+            "protoactor::message::any_message::tests::TestMessage",
+            message.type_name()
+        );
+    }
+
+    #[test]
+    fn should_unwrap_only_when_not_shared() {
+        let message = AnyMessage::new(TestMessage(1));
+        let clone = message.clone();
+
+        let message = message.try_unwrap::<TestMessage>().unwrap_err();
+        drop(clone);
+
+        assert_eq!(TestMessage(1), message.try_unwrap::<TestMessage>().unwrap());
+    }
+}
@@ -0,0 +1,59 @@
+use crate::message::{
+    DeadLetterResponse, Message, PoisonPill, Stop, Terminated, Touch, Touched, Unwatch, Watch,
+};
+
+/// Delivered to the actor as the first message after it is spawned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Started;
+
+/// Delivered to the actor when it starts stopping, before its children are stopped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stopping;
+
+/// Delivered to the actor as the last message, after it was removed from the registry.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stopped;
+
+impl Message for Started {
+    type Result = ();
+}
+
+impl Message for Stopping {
+    type Result = ();
+}
+
+impl Message for Stopped {
+    type Result = ();
+}
+
+impl Message for PoisonPill {
+    type Result = ();
+}
+
+impl Message for Stop {
+    type Result = ();
+}
+
+impl Message for Watch {
+    type Result = ();
+}
+
+impl Message for Unwatch {
+    type Result = ();
+}
+
+impl Message for Terminated {
+    type Result = ();
+}
+
+impl Message for DeadLetterResponse {
+    type Result = ();
+}
+
+impl Message for Touch {
+    type Result = Touched;
+}
+
+impl Message for Touched {
+    type Result = ();
+}
@@ -9,6 +9,7 @@ pub type MessageHeader = HashMap<String, String>;
 /// Adds headers and sender information to a message.
 ///
 /// Message must implement [Message] trait.
+#[derive(Clone)]
 pub struct MessageEnvelope<M>
 where
     M: Message + Send,
@@ -50,6 +51,12 @@ where
         &self.message
     }
 
+    /// Unwraps the message, dropping sender and headers.
+    #[inline]
+    pub fn into_message(self) -> M {
+        self.message
+    }
+
     #[inline]
     pub fn get_sender(&self) -> &Option<Pid> {
         &self.sender
@@ -0,0 +1,22 @@
+use super::Pid;
+use std::fmt::{Display, Formatter};
+
+impl Pid {
+    pub fn new<A, I>(address: A, id: I) -> Self
+    where
+        A: Into<String>,
+        I: Into<String>,
+    {
+        Self {
+            address: address.into(),
+            id: id.into(),
+            request_id: 0,
+        }
+    }
+}
+
+impl Display for Pid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}/{}", self.address, self.id)
+    }
+}
@@ -0,0 +1,221 @@
+//! Actor system metrics.
+//!
+//! When metrics are enabled with [ActorSystemConfig::with_metrics], the actor system reports
+//! counters and histograms to a [MetricsRecorder]. Recorder can be plugged in with
+//! [ActorSystemConfig::with_metrics_recorder], otherwise metrics are kept in
+//! [InMemoryMetricsRecorder]. Metrics kept by the recorder can be exposed to Prometheus with
+//! [PrometheusExporter].
+//!
+//! [ActorSystemConfig::with_metrics]: crate::system::config::ActorSystemConfig::with_metrics
+//! [ActorSystemConfig::with_metrics_recorder]: crate::system::config::ActorSystemConfig::with_metrics_recorder
+mod in_memory_recorder;
+mod prometheus;
+
+pub use in_memory_recorder::*;
+pub use prometheus::*;
+
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+pub const ACTOR_SPAWN_COUNT: &str = "protoactor_actor_spawn_count";
+pub const ACTOR_STOPPED_COUNT: &str = "protoactor_actor_stopped_count";
+pub const ACTOR_MAILBOX_LENGTH: &str = "protoactor_actor_mailbox_length";
+pub const ACTOR_MESSAGE_RECEIVE_DURATION: &str = "protoactor_actor_messagereceive_duration_seconds";
+pub const DEAD_LETTER_COUNT: &str = "protoactor_deadletter_count";
+pub const FUTURE_TIMED_OUT_COUNT: &str = "protoactor_future_timedout_count";
+
+/// Label with address of the actor system that reported the metric.
+pub const ADDRESS_LABEL: &str = "address";
+/// Label with type name of the actor that reported the metric.
+pub const ACTOR_TYPE_LABEL: &str = "actortype";
+/// Label with type name of the message, see [DiagnosticsTypeName](crate::diagnostics::DiagnosticsTypeName).
+pub const MESSAGE_TYPE_LABEL: &str = "messagetype";
+
+pub type Labels = Vec<(&'static str, String)>;
+
+/// Receives metrics reported by the actor system.
+pub trait MetricsRecorder
+where
+    Self: Send + Sync + 'static,
+{
+    fn increment_counter(&self, name: &'static str, labels: &Labels);
+
+    fn record_histogram(&self, name: &'static str, labels: &Labels, value: f64);
+
+    /// Current values of recorded metrics, used by exporters such as [PrometheusExporter].
+    /// Recorders that forward metrics elsewhere keep the default empty snapshot.
+    fn snapshot(&self) -> Vec<(MetricKey, MetricValue)> {
+        Vec::new()
+    }
+}
+
+impl Debug for dyn MetricsRecorder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "MetricsRecorder")
+    }
+}
+
+/// Upper bounds of histogram buckets used for the metric.
+pub fn histogram_buckets(name: &str) -> &'static [f64] {
+    match name {
+        ACTOR_MAILBOX_LENGTH => &[0.0, 1.0, 10.0, 100.0, 1_000.0, 10_000.0],
+        _ => &[
+            0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+        ],
+    }
+}
+
+/// Reports actor system metrics to the configured [MetricsRecorder].
+pub struct ActorMetrics {
+    address: RwLock<String>,
+    recorder: Arc<dyn MetricsRecorder>,
+}
+
+impl ActorMetrics {
+    pub(crate) fn new(address: String, recorder: Arc<dyn MetricsRecorder>) -> Self {
+        Self {
+            address: RwLock::new(address),
+            recorder,
+        }
+    }
+
+    pub(crate) fn set_address(&self, address: String) {
+        *self.address.write().unwrap() = address;
+    }
+
+    fn address(&self) -> String {
+        self.address.read().unwrap().clone()
+    }
+
+    #[inline]
+    pub fn recorder(&self) -> &Arc<dyn MetricsRecorder> {
+        &self.recorder
+    }
+
+    pub(crate) fn actor_spawned(&self, actor_type: &str) {
+        self.recorder
+            .increment_counter(ACTOR_SPAWN_COUNT, &self.actor_labels(actor_type));
+    }
+
+    pub(crate) fn actor_stopped(&self, actor_type: &str) {
+        self.recorder
+            .increment_counter(ACTOR_STOPPED_COUNT, &self.actor_labels(actor_type));
+    }
+
+    pub(crate) fn mailbox_length(&self, actor_type: &str, length: usize) {
+        self.recorder.record_histogram(
+            ACTOR_MAILBOX_LENGTH,
+            &self.actor_labels(actor_type),
+            length as f64,
+        );
+    }
+
+    pub(crate) fn message_received(
+        &self,
+        actor_type: &str,
+        message_type: String,
+        elapsed: Duration,
+    ) {
+        let mut labels = self.actor_labels(actor_type);
+        labels.push((MESSAGE_TYPE_LABEL, message_type));
+        self.recorder.record_histogram(
+            ACTOR_MESSAGE_RECEIVE_DURATION,
+            &labels,
+            elapsed.as_secs_f64(),
+        );
+    }
+
+    pub(crate) fn dead_letter(&self, message_type: String) {
+        let labels = vec![
+            (ADDRESS_LABEL, self.address()),
+            (MESSAGE_TYPE_LABEL, message_type),
+        ];
+        self.recorder.increment_counter(DEAD_LETTER_COUNT, &labels);
+    }
+
+    pub(crate) fn future_timed_out(&self) {
+        let labels = vec![(ADDRESS_LABEL, self.address())];
+        self.recorder
+            .increment_counter(FUTURE_TIMED_OUT_COUNT, &labels);
+    }
+
+    fn actor_labels(&self, actor_type: &str) -> Labels {
+        vec![
+            (ADDRESS_LABEL, self.address()),
+            (ACTOR_TYPE_LABEL, actor_type.to_string()),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actor::{Actor, Props};
+    use crate::context::{ActorContext, SenderContext};
+    use crate::message::Message;
+    use crate::metrics::*;
+    use crate::system::config::ActorSystemConfig;
+    use crate::system::ActorSystem;
+    use async_trait::async_trait;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    struct Ping;
+
+    /// Not responded to.
+    struct Ignored;
+
+    impl Message for Ping {
+        type Result = Ping;
+    }
+
+    impl Message for Ignored {
+        type Result = ();
+    }
+
+    struct TestActor;
+
+    #[async_trait]
+    impl Actor for TestActor {
+        async fn receive(&mut self, ctx: &mut ActorContext) {
+            let message = ctx.get_message();
+            if message.is::<Ping>() {
+                ctx.respond(Ping);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn should_record_actor_metrics() {
+        let recorder = Arc::new(InMemoryMetricsRecorder::new());
+        let config = ActorSystemConfig::setup()
+            .with_metrics_recorder(recorder.clone())
+            .with_actor_request_timeout(Duration::from_millis(20));
+        let system = ActorSystem::with_config(config);
+        let root = system.root();
+        let actor_type = ("actortype", "protoactor::metrics::tests::TestActor");
+        let ping_type = ("messagetype", "protoactor::metrics::tests::Ping");
+
+        let pid = root.spawn(Props::from_producer(|| TestActor));
+        let ignored = root.request_future(&pid, Ignored).await;
+        root.request_future(&pid, Ping).await.unwrap();
+        root.stop(&pid);
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        root.send(&pid, Ping);
+        let dead = root.request_future(&pid, Ping).await;
+
+        assert!(ignored.is_err());
+        assert!(dead.is_err());
+        assert_eq!(1, recorder.counter(ACTOR_SPAWN_COUNT, &[actor_type]));
+        assert_eq!(1, recorder.counter(ACTOR_STOPPED_COUNT, &[actor_type]));
+        assert_eq!(2, recorder.counter(DEAD_LETTER_COUNT, &[ping_type]));
+        assert_eq!(1, recorder.counter(FUTURE_TIMED_OUT_COUNT, &[]));
+        let duration = recorder
+            .histogram(ACTOR_MESSAGE_RECEIVE_DURATION, &[actor_type, ping_type])
+            .unwrap();
+        assert_eq!(1, duration.count);
+        assert!(recorder
+            .histogram(ACTOR_MAILBOX_LENGTH, &[actor_type])
+            .is_some());
+    }
+}
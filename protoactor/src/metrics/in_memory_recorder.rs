@@ -0,0 +1,169 @@
+use crate::metrics::{histogram_buckets, Labels, MetricsRecorder};
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// Upper bounds of the buckets.
+    pub buckets: &'static [f64],
+    /// Number of observations per bucket, not cumulative. Last element counts observations
+    /// larger than any bucket bound.
+    pub bucket_counts: Vec<u64>,
+    pub count: u64,
+    pub sum: f64,
+}
+
+impl Histogram {
+    fn new(buckets: &'static [f64]) -> Self {
+        Self {
+            buckets,
+            bucket_counts: vec![0; buckets.len() + 1],
+            count: 0,
+            sum: 0.0,
+        }
+    }
+
+    fn record(&mut self, value: f64) {
+        let bucket = self
+            .buckets
+            .iter()
+            .position(|bound| value <= *bound)
+            .unwrap_or(self.buckets.len());
+        self.bucket_counts[bucket] += 1;
+        self.count += 1;
+        self.sum += value;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetricValue {
+    Counter(u64),
+    Histogram(Histogram),
+}
+
+/// Single metric series, metric name with a set of labels.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct MetricKey {
+    pub name: &'static str,
+    /// Labels sorted by name.
+    pub labels: Vec<(&'static str, String)>,
+}
+
+impl MetricKey {
+    fn new(name: &'static str, labels: &Labels) -> Self {
+        let mut labels = labels.clone();
+        labels.sort();
+        Self { name, labels }
+    }
+}
+
+/// [MetricsRecorder] that keeps all metrics in memory.
+#[derive(Debug, Default)]
+pub struct InMemoryMetricsRecorder {
+    metrics: Mutex<BTreeMap<MetricKey, MetricValue>>,
+}
+
+impl InMemoryMetricsRecorder {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Sum of the counter across all series that contain given labels.
+    pub fn counter(&self, name: &str, labels: &[(&str, &str)]) -> u64 {
+        self.metrics
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(key, _)| key.name == name && Self::has_labels(key, labels))
+            .map(|(_, value)| match value {
+                MetricValue::Counter(count) => *count,
+                MetricValue::Histogram(_) => 0,
+            })
+            .sum()
+    }
+
+    /// Histogram of the first series that contains given labels.
+    pub fn histogram(&self, name: &str, labels: &[(&str, &str)]) -> Option<Histogram> {
+        self.metrics
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(key, _)| key.name == name && Self::has_labels(key, labels))
+            .find_map(|(_, value)| match value {
+                MetricValue::Histogram(histogram) => Some(histogram.clone()),
+                MetricValue::Counter(_) => None,
+            })
+    }
+
+    fn has_labels(key: &MetricKey, labels: &[(&str, &str)]) -> bool {
+        labels.iter().all(|(name, value)| {
+            key.labels
+                .iter()
+                .any(|(key_name, key_value)| key_name == name && key_value == value)
+        })
+    }
+}
+
+impl MetricsRecorder for InMemoryMetricsRecorder {
+    fn increment_counter(&self, name: &'static str, labels: &Labels) {
+        let mut metrics = self.metrics.lock().unwrap();
+        let value = metrics
+            .entry(MetricKey::new(name, labels))
+            .or_insert(MetricValue::Counter(0));
+        if let MetricValue::Counter(count) = value {
+            *count += 1;
+        }
+    }
+
+    fn record_histogram(&self, name: &'static str, labels: &Labels, value: f64) {
+        let mut metrics = self.metrics.lock().unwrap();
+        let histogram = metrics
+            .entry(MetricKey::new(name, labels))
+            .or_insert_with(|| MetricValue::Histogram(Histogram::new(histogram_buckets(name))));
+        if let MetricValue::Histogram(histogram) = histogram {
+            histogram.record(value);
+        }
+    }
+
+    /// Copy of all recorded metrics, ordered by metric name and labels.
+    fn snapshot(&self) -> Vec<(MetricKey, MetricValue)> {
+        self.metrics
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::metrics::{InMemoryMetricsRecorder, MetricsRecorder, ACTOR_MAILBOX_LENGTH};
+
+    #[test]
+    fn should_sum_counters_matching_labels() {
+        let recorder = InMemoryMetricsRecorder::new();
+
+        recorder.increment_counter("count", &vec![("a", "1".into()), ("b", "1".into())]);
+        recorder.increment_counter("count", &vec![("b", "1".into()), ("a", "1".into())]);
+        recorder.increment_counter("count", &vec![("a", "2".into()), ("b", "1".into())]);
+
+        assert_eq!(3, recorder.counter("count", &[("b", "1")]));
+        assert_eq!(2, recorder.counter("count", &[("a", "1")]));
+        assert_eq!(0, recorder.counter("other", &[]));
+    }
+
+    #[test]
+    fn should_record_histogram_buckets() {
+        let recorder = InMemoryMetricsRecorder::new();
+
+        for value in [0.0, 5.0, 5.0, 20_000.0] {
+            recorder.record_histogram(ACTOR_MAILBOX_LENGTH, &vec![], value);
+        }
+        let histogram = recorder.histogram(ACTOR_MAILBOX_LENGTH, &[]).unwrap();
+
+        assert_eq!(4, histogram.count);
+        assert_eq!(20_010.0, histogram.sum);
+        assert_eq!(vec![1, 0, 2, 0, 0, 0, 1], histogram.bucket_counts);
+    }
+}
@@ -0,0 +1,231 @@
+use crate::metrics::{
+    MetricKey, MetricValue, ACTOR_MAILBOX_LENGTH, ACTOR_MESSAGE_RECEIVE_DURATION,
+    ACTOR_SPAWN_COUNT, ACTOR_STOPPED_COUNT, DEAD_LETTER_COUNT, FUTURE_TIMED_OUT_COUNT,
+};
+use crate::system::ActorSystem;
+use log::debug;
+use std::fmt::Write;
+use std::io;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+
+/// Renders metrics of the actor system in Prometheus text exposition format.
+///
+/// Metrics are read from the [MetricsRecorder](crate::metrics::MetricsRecorder) snapshot, so
+/// the actor system must be configured with metrics enabled and a recorder that keeps them,
+/// such as the default [InMemoryMetricsRecorder](crate::metrics::InMemoryMetricsRecorder).
+#[derive(Clone)]
+pub struct PrometheusExporter {
+    system: Arc<ActorSystem>,
+}
+
+impl PrometheusExporter {
+    pub fn new(system: Arc<ActorSystem>) -> Self {
+        Self { system }
+    }
+
+    /// Current metrics in Prometheus text format, empty if metrics are disabled.
+    pub fn render(&self) -> String {
+        match self.system.metrics() {
+            Some(metrics) => encode_prometheus(&metrics.recorder().snapshot()),
+            None => String::new(),
+        }
+    }
+
+    /// Binds local HTTP port and serves metrics on `GET /metrics` until the task is dropped.
+    pub async fn serve(self, addr: &str) -> io::Result<()> {
+        self.serve_listener(TcpListener::bind(addr).await?).await
+    }
+
+    /// Serves metrics on `GET /metrics` for connections accepted by the listener.
+    pub async fn serve_listener(self, listener: TcpListener) -> io::Result<()> {
+        loop {
+            let (stream, peer) = listener.accept().await?;
+            let exporter = self.clone();
+            tokio::spawn(async move {
+                if let Err(error) = exporter.handle_connection(stream).await {
+                    debug!("Failed to serve metrics to {}: {}", peer, error);
+                }
+            });
+        }
+    }
+
+    async fn handle_connection(&self, mut stream: TcpStream) -> io::Result<()> {
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        while !request.windows(4).any(|window| window == b"\r\n\r\n") {
+            let read = stream.read(&mut buffer).await?;
+            if read == 0 || request.len() + read > MAX_REQUEST_SIZE {
+                return Ok(());
+            }
+            request.extend_from_slice(&buffer[..read]);
+        }
+        let request_line = request.split(|byte| *byte == b'\r').next().unwrap_or(&[]);
+        let mut parts = request_line.split(|byte| *byte == b' ');
+        let response = match (parts.next(), parts.next()) {
+            (Some(b"GET"), Some(b"/metrics")) => {
+                let body = self.render();
+                format!(
+                    "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    CONTENT_TYPE,
+                    body.len(),
+                    body
+                )
+            }
+            _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                .to_string(),
+        };
+        stream.write_all(response.as_bytes()).await?;
+        stream.shutdown().await
+    }
+}
+
+/// Encodes metrics snapshot in Prometheus text exposition format.
+///
+/// Snapshot must be ordered by metric name, as returned by
+/// [MetricsRecorder::snapshot](crate::metrics::MetricsRecorder::snapshot).
+pub fn encode_prometheus(snapshot: &[(MetricKey, MetricValue)]) -> String {
+    let mut output = String::new();
+    let mut current = None;
+    for (key, value) in snapshot {
+        if current != Some(key.name) {
+            current = Some(key.name);
+            let metric_type = match value {
+                MetricValue::Counter(_) => "counter",
+                MetricValue::Histogram(_) => "histogram",
+            };
+            if let Some(help) = metric_help(key.name) {
+                let _ = writeln!(output, "# HELP {} {}", key.name, help);
+            }
+            let _ = writeln!(output, "# TYPE {} {}", key.name, metric_type);
+        }
+        match value {
+            MetricValue::Counter(count) => {
+                let _ = writeln!(output, "{}{} {}", key.name, encode_labels(key, None), count);
+            }
+            MetricValue::Histogram(histogram) => {
+                let mut cumulative = 0;
+                let bounds = histogram.buckets.iter().map(|bound| bound.to_string());
+                let bounds = bounds.chain(std::iter::once("+Inf".to_string()));
+                for (bound, count) in bounds.zip(&histogram.bucket_counts) {
+                    cumulative += count;
+                    let labels = encode_labels(key, Some(&bound));
+                    let _ = writeln!(output, "{}_bucket{} {}", key.name, labels, cumulative);
+                }
+                let labels = encode_labels(key, None);
+                let _ = writeln!(output, "{}_sum{} {}", key.name, labels, histogram.sum);
+                let _ = writeln!(output, "{}_count{} {}", key.name, labels, histogram.count);
+            }
+        }
+    }
+    output
+}
+
+fn metric_help(name: &str) -> Option<&'static str> {
+    match name {
+        ACTOR_SPAWN_COUNT => Some("Number of spawned actors"),
+        ACTOR_STOPPED_COUNT => Some("Number of stopped actors"),
+        ACTOR_MAILBOX_LENGTH => Some("Actor mailbox length"),
+        ACTOR_MESSAGE_RECEIVE_DURATION => Some("Actor message receive duration in seconds"),
+        DEAD_LETTER_COUNT => Some("Number of dead letters"),
+        FUTURE_TIMED_OUT_COUNT => Some("Number of timed out futures"),
+        _ => None,
+    }
+}
+
+fn encode_labels(key: &MetricKey, le: Option<&str>) -> String {
+    let labels = key
+        .labels
+        .iter()
+        .map(|(name, value)| (*name, value.as_str()))
+        .chain(le.map(|le| ("le", le)))
+        .map(|(name, value)| format!("{}=\"{}\"", name, escape_label_value(value)))
+        .collect::<Vec<_>>();
+    if labels.is_empty() {
+        String::new()
+    } else {
+        format!("{{{}}}", labels.join(","))
+    }
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actor::{Actor, Props};
+    use crate::context::ActorContext;
+    use crate::metrics::{
+        encode_prometheus, InMemoryMetricsRecorder, MetricsRecorder, PrometheusExporter,
+        ACTOR_MAILBOX_LENGTH,
+    };
+    use crate::system::config::ActorSystemConfig;
+    use crate::system::ActorSystem;
+    use async_trait::async_trait;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+
+    struct EmptyActor;
+
+    #[async_trait]
+    impl Actor for EmptyActor {
+        async fn receive(&mut self, _: &mut ActorContext) {}
+    }
+
+    #[test]
+    fn should_encode_counters_and_histograms() {
+        let recorder = InMemoryMetricsRecorder::new();
+        recorder.increment_counter("count", &vec![("name", "a \"quoted\"\nvalue".into())]);
+        recorder.record_histogram(ACTOR_MAILBOX_LENGTH, &vec![("name", "b".into())], 5.0);
+        recorder.record_histogram(ACTOR_MAILBOX_LENGTH, &vec![("name", "b".into())], 0.0);
+
+        let expected = r#"# TYPE count counter
+count{name="a \"quoted\"\nvalue"} 1
+# HELP protoactor_actor_mailbox_length Actor mailbox length
+# TYPE protoactor_actor_mailbox_length histogram
+protoactor_actor_mailbox_length_bucket{name="b",le="0"} 1
+protoactor_actor_mailbox_length_bucket{name="b",le="1"} 1
+protoactor_actor_mailbox_length_bucket{name="b",le="10"} 2
+protoactor_actor_mailbox_length_bucket{name="b",le="100"} 2
+protoactor_actor_mailbox_length_bucket{name="b",le="1000"} 2
+protoactor_actor_mailbox_length_bucket{name="b",le="10000"} 2
+protoactor_actor_mailbox_length_bucket{name="b",le="+Inf"} 2
+protoactor_actor_mailbox_length_sum{name="b"} 5
+protoactor_actor_mailbox_length_count{name="b"} 2
+"#;
+        assert_eq!(expected, encode_prometheus(&recorder.snapshot()));
+    }
+
+    #[tokio::test]
+    async fn should_serve_actor_system_metrics() {
+        let config = ActorSystemConfig::setup().with_metrics(true);
+        let system = ActorSystem::with_config(config);
+        system.root().spawn(Props::from_producer(|| EmptyActor));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(PrometheusExporter::new(system).serve_listener(listener));
+
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        server.abort();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains(concat!(
+            "protoactor_actor_spawn_count{actortype=",
+            "\"protoactor::metrics::prometheus::tests::EmptyActor\",address=\"nohost\"} 1\n"
+        )));
+    }
+}
@@ -0,0 +1,568 @@
+//! Receiver, sender and spawn middleware.
+//!
+//! Middleware is attached through [Props] and [RootContext](crate::context::RootContext), and
+//! runs in the order it was added. Each middleware gets the next stage of the pipeline and
+//! decides whether to call it, so it can inspect or modify the [MessageEnvelope], short-circuit
+//! the pipeline or wrap the rest of it.
+//!
+//! * [ReceiverMiddleware] wraps every message received by actors spawned from the [Props].
+//! * [SenderMiddleware] wraps every message sent by actors spawned from the [Props], or sent
+//!   from the root context.
+//! * [SpawnMiddleware] wraps every actor spawned by actors spawned from the [Props], or spawned
+//!   from the root context.
+//!
+//! All pipelines are async, while [SenderContext::send] and spawning return right away. Their
+//! pipelines run until a middleware awaits, and complete on a task of their own afterwards.
+//! Messages sent from a context pass its sender pipeline one after another, so they are
+//! delivered in the order they were sent. Spawning returns the [Pid] under the requested name,
+//! messages sent to it before the pipeline spawns the actor are kept until it does, or go to
+//! dead letters if it spawns no actor under the name.
+//!
+//! [SenderContext::send]: crate::context::SenderContext::send
+use crate::actor::Props;
+use crate::context::ActorContext;
+use crate::message::{AnyMessage, MessageEnvelope, Pid};
+use crate::process::{SpawnError, SpawningProcess};
+use crate::system::ActorSystem;
+use async_trait::async_trait;
+use futures::future::BoxFuture;
+use futures::task::noop_waker_ref;
+use futures::FutureExt;
+use log::warn;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+/// Wraps receives, awaiting the rest of the pipeline with [ReceiverNext::run].
+#[async_trait]
+pub trait ReceiverMiddleware
+where
+    Self: Send + Sync + 'static,
+{
+    async fn receive(
+        &self,
+        ctx: &mut ActorContext,
+        envelope: MessageEnvelope<AnyMessage>,
+        next: ReceiverNext<'_>,
+    );
+}
+
+/// Wraps sends from the actor `from`, or from the root context if [None], awaiting the rest of
+/// the pipeline with [SenderNext::run].
+#[async_trait]
+pub trait SenderMiddleware
+where
+    Self: Send + Sync + 'static,
+{
+    async fn send(
+        &self,
+        system: &Arc<ActorSystem>,
+        from: Option<&Pid>,
+        target: &Pid,
+        envelope: MessageEnvelope<AnyMessage>,
+        next: SenderNext<'_>,
+    );
+}
+
+/// Wraps spawns, awaiting the rest of the pipeline with [SpawnNext::run].
+#[async_trait]
+pub trait SpawnMiddleware
+where
+    Self: Send + Sync + 'static,
+{
+    async fn spawn(
+        &self,
+        system: &Arc<ActorSystem>,
+        name: String,
+        props: &Props,
+        parent: Option<&Pid>,
+        next: SpawnNext<'_>,
+    ) -> Result<Pid, SpawnError>;
+}
+
+/// Rest of the receiver pipeline, ends with [Actor::receive](crate::actor::Actor::receive).
+pub struct ReceiverNext<'a> {
+    middleware: &'a [Arc<dyn ReceiverMiddleware>],
+}
+
+impl<'a> ReceiverNext<'a> {
+    pub(crate) fn new(middleware: &'a [Arc<dyn ReceiverMiddleware>]) -> Self {
+        Self { middleware }
+    }
+
+    pub async fn run(self, ctx: &mut ActorContext, envelope: MessageEnvelope<AnyMessage>) {
+        match self.middleware.split_first() {
+            Some((middleware, rest)) => {
+                middleware
+                    .receive(ctx, envelope, ReceiverNext::new(rest))
+                    .await
+            }
+            None => ctx.receive_envelope(envelope).await,
+        }
+    }
+}
+
+/// Rest of the sender pipeline, ends with delivery to the target process.
+pub struct SenderNext<'a> {
+    middleware: &'a [Arc<dyn SenderMiddleware>],
+}
+
+impl<'a> SenderNext<'a> {
+    pub(crate) fn new(middleware: &'a [Arc<dyn SenderMiddleware>]) -> Self {
+        Self { middleware }
+    }
+
+    pub async fn run(
+        self,
+        system: &Arc<ActorSystem>,
+        from: Option<&Pid>,
+        target: &Pid,
+        envelope: MessageEnvelope<AnyMessage>,
+    ) {
+        match self.middleware.split_first() {
+            Some((middleware, rest)) => {
+                middleware
+                    .send(system, from, target, envelope, SenderNext::new(rest))
+                    .await
+            }
+            None => system
+                .get_process(target)
+                .send_user_message(target, envelope),
+        }
+    }
+}
+
+/// Rest of the spawn pipeline, ends with registering and starting the actor.
+pub struct SpawnNext<'a> {
+    middleware: &'a [Arc<dyn SpawnMiddleware>],
+    /// Process holding the requested name until the actor is registered under it.
+    reserved: Option<&'a Arc<SpawningProcess>>,
+}
+
+impl<'a> SpawnNext<'a> {
+    pub async fn run(
+        self,
+        system: &Arc<ActorSystem>,
+        name: String,
+        props: &Props,
+        parent: Option<&Pid>,
+    ) -> Result<Pid, SpawnError> {
+        match self.middleware.split_first() {
+            Some((middleware, rest)) => {
+                let next = SpawnNext {
+                    middleware: rest,
+                    reserved: self.reserved,
+                };
+                middleware.spawn(system, name, props, parent, next).await
+            }
+            None => match self.reserved {
+                Some(reserved) => {
+                    let id = name.clone();
+                    system
+                        .registry()
+                        .claim(&id, reserved, || props.spawn(system, name, parent.cloned()))
+                }
+                None => props.spawn(system, name, parent.cloned()),
+            },
+        }
+    }
+}
+
+/// Sends the envelope through the sender `middleware` of a context, in the order of the sends
+/// of the context.
+#[derive(Clone, Default)]
+pub(crate) struct SendQueue {
+    state: Arc<Mutex<SendQueueState>>,
+}
+
+#[derive(Default)]
+struct SendQueueState {
+    /// Whether a send is in the pipeline, later ones wait in `pending`.
+    running: bool,
+    pending: VecDeque<BoxFuture<'static, ()>>,
+}
+
+impl SendQueue {
+    pub(crate) fn send(
+        &self,
+        middleware: &[Arc<dyn SenderMiddleware>],
+        system: &Arc<ActorSystem>,
+        from: Option<&Pid>,
+        target: &Pid,
+        envelope: MessageEnvelope<AnyMessage>,
+    ) {
+        if middleware.is_empty() {
+            return system
+                .get_process(target)
+                .send_user_message(target, envelope);
+        }
+        let (middleware, system) = (middleware.to_vec(), system.clone());
+        let (from, target) = (from.cloned(), target.clone());
+        let send = async move {
+            SenderNext::new(&middleware)
+                .run(&system, from.as_ref(), &target, envelope)
+                .await
+        };
+        let mut state = self.state.lock().unwrap();
+        if state.running {
+            state.pending.push_back(send.boxed());
+            return;
+        }
+        state.running = true;
+        drop(state);
+        self.run(send.boxed());
+    }
+
+    /// Runs sends until one awaits, which continues on a task that runs the rest after it.
+    fn run(&self, mut send: BoxFuture<'static, ()>) {
+        loop {
+            if poll_now(&mut send).is_pending() {
+                let queue = self.clone();
+                tokio::spawn(async move {
+                    send.await;
+                    if let Some(next) = queue.next() {
+                        queue.run(next);
+                    }
+                });
+                return;
+            }
+            match self.next() {
+                Some(next) => send = next,
+                None => return,
+            }
+        }
+    }
+
+    fn next(&self) -> Option<BoxFuture<'static, ()>> {
+        let mut state = self.state.lock().unwrap();
+        let next = state.pending.pop_front();
+        state.running = next.is_some();
+        next
+    }
+}
+
+/// Spawns the actor through the spawn `middleware`. If a middleware awaits, the rest of the
+/// pipeline completes on a task of its own and the [Pid] under `name` is returned right away.
+pub(crate) fn spawn(
+    middleware: &[Arc<dyn SpawnMiddleware>],
+    system: &Arc<ActorSystem>,
+    name: String,
+    props: &Props,
+    parent: Option<&Pid>,
+) -> Result<Pid, SpawnError> {
+    if middleware.is_empty() {
+        return props.spawn(system, name, parent.cloned());
+    }
+    let reserved = Arc::new(SpawningProcess::new(system));
+    let pid = system.registry().add(name.clone(), reserved.clone())?;
+    let (middleware, system, props) = (middleware.to_vec(), system.clone(), props.clone());
+    let (parent, reserved_pid) = (parent.cloned(), pid.clone());
+    let mut spawn = async move {
+        let next = SpawnNext {
+            middleware: &middleware,
+            reserved: Some(&reserved),
+        };
+        let result = next.run(&system, name, &props, parent.as_ref()).await;
+        reserved.complete(&reserved_pid, parent.as_ref());
+        result
+    }
+    .boxed();
+    match poll_now(&mut spawn) {
+        Poll::Ready(result) => result,
+        Poll::Pending => {
+            let spawning = pid.clone();
+            tokio::spawn(async move {
+                if let Err(error) = spawn.await {
+                    warn!("Failed to spawn {}: {}", spawning, error);
+                }
+            });
+            Ok(pid)
+        }
+    }
+}
+
+/// Polls the future once, without waking anything up once it can make progress.
+fn poll_now<T>(future: &mut BoxFuture<'static, T>) -> Poll<T> {
+    future.poll_unpin(&mut Context::from_waker(noop_waker_ref()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actor::{Actor, Props};
+    use crate::context::{ActorContext, SenderContext};
+    use crate::message::{AnyMessage, Message, MessageEnvelope, Pid};
+    use crate::middleware::{
+        ReceiverMiddleware, ReceiverNext, SenderMiddleware, SenderNext, SpawnMiddleware, SpawnNext,
+    };
+    use crate::process::{RequestError, SpawnError};
+    use crate::system::config::ActorSystemConfig;
+    use crate::system::ActorSystem;
+    use async_trait::async_trait;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    struct Echo;
+
+    struct Reply(Option<String>);
+
+    struct Forward(Pid);
+
+    impl Message for Echo {
+        type Result = Reply;
+    }
+
+    impl Message for Reply {
+        type Result = ();
+    }
+
+    impl Message for Forward {
+        type Result = Reply;
+    }
+
+    /// Responds with the value of `tenant` header.
+    struct EchoActor;
+
+    #[async_trait]
+    impl Actor for EchoActor {
+        async fn receive(&mut self, ctx: &mut ActorContext) {
+            let message = ctx.get_message();
+            if message.is::<Echo>() {
+                ctx.respond(Reply(ctx.get_headers().get("tenant").cloned()));
+            } else if let Some(Forward(target)) = message.downcast_ref::<Forward>() {
+                let response = ctx.request_future(target, Echo);
+                ctx.reenter_after(response, |_: &mut EchoActor, ctx, reply| {
+                    ctx.respond(reply.unwrap())
+                });
+            }
+        }
+    }
+
+    struct AddTenant;
+
+    #[async_trait]
+    impl ReceiverMiddleware for AddTenant {
+        async fn receive(
+            &self,
+            ctx: &mut ActorContext,
+            mut envelope: MessageEnvelope<AnyMessage>,
+            next: ReceiverNext<'_>,
+        ) {
+            envelope.merge_header([("tenant".to_string(), "acme".to_string())].into());
+            next.run(ctx, envelope).await
+        }
+    }
+
+    struct RejectEcho;
+
+    #[async_trait]
+    impl ReceiverMiddleware for RejectEcho {
+        async fn receive(
+            &self,
+            ctx: &mut ActorContext,
+            envelope: MessageEnvelope<AnyMessage>,
+            next: ReceiverNext<'_>,
+        ) {
+            if envelope.get_message().is::<Echo>() {
+                return;
+            }
+            next.run(ctx, envelope).await
+        }
+    }
+
+    #[async_trait]
+    impl SenderMiddleware for AddTenant {
+        async fn send(
+            &self,
+            system: &Arc<ActorSystem>,
+            from: Option<&Pid>,
+            target: &Pid,
+            mut envelope: MessageEnvelope<AnyMessage>,
+            next: SenderNext<'_>,
+        ) {
+            envelope.merge_header([("tenant".to_string(), "sender".to_string())].into());
+            next.run(system, from, target, envelope).await
+        }
+    }
+
+    /// Holds back the first message for a while.
+    #[derive(Default)]
+    struct DelayFirst(AtomicUsize);
+
+    #[async_trait]
+    impl SenderMiddleware for DelayFirst {
+        async fn send(
+            &self,
+            system: &Arc<ActorSystem>,
+            from: Option<&Pid>,
+            target: &Pid,
+            envelope: MessageEnvelope<AnyMessage>,
+            next: SenderNext<'_>,
+        ) {
+            if self.0.fetch_add(1, Ordering::Relaxed) == 0 {
+                tokio::time::sleep(Duration::from_millis(20)).await;
+            }
+            next.run(system, from, target, envelope).await
+        }
+    }
+
+    #[derive(Default)]
+    struct CountSpawns(AtomicUsize);
+
+    #[async_trait]
+    impl SpawnMiddleware for Arc<CountSpawns> {
+        async fn spawn(
+            &self,
+            system: &Arc<ActorSystem>,
+            name: String,
+            props: &Props,
+            parent: Option<&Pid>,
+            next: SpawnNext<'_>,
+        ) -> Result<Pid, SpawnError> {
+            self.0.fetch_add(1, Ordering::Relaxed);
+            next.run(system, name, props, parent).await
+        }
+    }
+
+    /// Awaits before spawning, short-circuits if `allow` is `false`.
+    struct Authorize {
+        allow: bool,
+    }
+
+    #[async_trait]
+    impl SpawnMiddleware for Authorize {
+        async fn spawn(
+            &self,
+            system: &Arc<ActorSystem>,
+            name: String,
+            props: &Props,
+            parent: Option<&Pid>,
+            next: SpawnNext<'_>,
+        ) -> Result<Pid, SpawnError> {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            if !self.allow {
+                return Ok(Pid::new(system.address(), name));
+            }
+            next.run(system, name, props, parent).await
+        }
+    }
+
+    /// Records the values of received [Reply]s.
+    struct Collector(Arc<Mutex<Vec<Option<String>>>>);
+
+    #[async_trait]
+    impl Actor for Collector {
+        async fn receive(&mut self, ctx: &mut ActorContext) {
+            if let Some(Reply(value)) = ctx.get_message().downcast_ref::<Reply>() {
+                self.0.lock().unwrap().push(value.clone());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn receiver_middleware_should_modify_envelope() {
+        let system = ActorSystem::new();
+        let root = system.root();
+        let props = Props::from_producer(|| EchoActor).with_receiver_middleware(AddTenant);
+        let pid = root.spawn(props);
+
+        let reply = root.request_future(&pid, Echo).await.unwrap();
+
+        assert_eq!(Some("acme".to_string()), reply.0);
+    }
+
+    #[tokio::test]
+    async fn receiver_middleware_should_short_circuit() {
+        let config =
+            ActorSystemConfig::setup().with_actor_request_timeout(Duration::from_millis(50));
+        let system = ActorSystem::with_config(config);
+        let root = system.root();
+        let props = Props::from_producer(|| EchoActor).with_receiver_middleware(RejectEcho);
+        let pid = root.spawn(props);
+
+        assert_eq!(
+            Some(RequestError::Timeout),
+            root.request_future(&pid, Echo).await.err()
+        );
+    }
+
+    #[tokio::test]
+    async fn sender_middleware_should_wrap_outgoing_messages() {
+        let system = ActorSystem::new();
+        let root = system.root();
+        let echo = root.spawn(Props::from_producer(|| EchoActor));
+        let forwarder =
+            root.spawn(Props::from_producer(|| EchoActor).with_sender_middleware(AddTenant));
+
+        let from_root = root.request_future(&echo, Echo).await.unwrap();
+        let forwarded = root
+            .request_future(&forwarder, Forward(echo))
+            .await
+            .unwrap();
+
+        assert_eq!(None, from_root.0);
+        assert_eq!(Some("sender".to_string()), forwarded.0);
+    }
+
+    #[tokio::test]
+    async fn spawn_middleware_should_wrap_spawns_of_the_context() {
+        let system = ActorSystem::new();
+        let spawns = Arc::new(CountSpawns::default());
+        let root = system.root().with_spawn_middleware(spawns.clone());
+
+        root.spawn(Props::from_producer(|| EchoActor));
+        system.root().spawn(Props::from_producer(|| EchoActor));
+
+        assert_eq!(1, spawns.0.load(Ordering::Relaxed));
+    }
+
+    #[tokio::test]
+    async fn sender_middleware_should_keep_order_of_sends_while_awaiting() {
+        let system = ActorSystem::new();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let collected = received.clone();
+        let collector = system
+            .root()
+            .spawn(Props::from_producer(move || Collector(collected.clone())));
+        let root = system.root().with_sender_middleware(DelayFirst::default());
+
+        for value in ["a", "b", "c"] {
+            root.send(&collector, Reply(Some(value.to_string())));
+        }
+        assert!(received.lock().unwrap().is_empty());
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let expected = ["a", "b", "c"].map(|value| Some(value.to_string()));
+        assert_eq!(expected.to_vec(), *received.lock().unwrap());
+    }
+
+    #[tokio::test]
+    async fn spawn_middleware_should_keep_messages_until_actor_is_spawned() {
+        let system = ActorSystem::new();
+        let root = system
+            .root()
+            .with_spawn_middleware(Authorize { allow: true });
+
+        let pid = root.spawn(Props::from_producer(|| EchoActor));
+        let reply = system.root().request_future(&pid, Echo).await.unwrap();
+
+        assert_eq!(None, reply.0);
+    }
+
+    #[tokio::test]
+    async fn spawn_middleware_should_dead_letter_messages_if_nothing_is_spawned() {
+        let system = ActorSystem::new();
+        let root = system
+            .root()
+            .with_spawn_middleware(Authorize { allow: false });
+
+        let pid = root.spawn(Props::from_producer(|| EchoActor));
+        let response = system.root().request_future(&pid, Echo).await;
+
+        assert_eq!(
+            Some(RequestError::DeadLetter(Some(pid.clone()))),
+            response.err()
+        );
+        assert!(system.registry().get(&pid).is_none());
+    }
+}
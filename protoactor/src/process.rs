@@ -1,27 +1,35 @@
 mod actor_process;
+mod dead_letter_process;
+mod future_process;
 mod registry;
+mod spawning_process;
 
 pub use self::actor_process::*;
+pub use self::dead_letter_process::*;
+pub use self::future_process::*;
 pub use self::registry::*;
-use crate::message::{Message, Pid, SystemMessage};
+pub(crate) use self::spawning_process::SpawningProcess;
+use crate::diagnostics::ProcessDiagnostics;
+use crate::message::{AnyMessage, MessageEnvelope, Pid, SystemMessage};
 use crate::system::ActorSystem;
 use std::sync::Arc;
 
-//todo: `fn send_user_message(&self, pid: &Pid, msg: impl Message);`
-
-pub trait Process {
+pub trait Process
+where
+    Self: Send + Sync,
+{
     fn system(&self) -> Arc<ActorSystem>;
 
+    fn send_user_message(&self, pid: &Pid, envelope: MessageEnvelope<AnyMessage>);
+
     fn send_system_message(&self, pid: &Pid, msg: SystemMessage);
 
     fn stop(&self, pid: &Pid) {
         self.send_system_message(pid, SystemMessage::Stop)
     }
-}
 
-pub trait ReceiverProcess {
-    fn send_user_message<M>(&self, pid: &Pid, msg: M)
-    where
-        M: Message + Sync + 'static,
-        M::Result: Sync + 'static;
+    /// What the process knows about itself, children are filled in by the actor system.
+    fn get_diagnostics(&self, pid: &Pid) -> ProcessDiagnostics {
+        ProcessDiagnostics::new(pid.clone(), std::any::type_name::<Self>())
+    }
 }
@@ -1,11 +1,38 @@
-use crate::message::{Message, Pid, SystemMessage};
-use crate::process::{Process, ReceiverProcess};
+use crate::diagnostics::ProcessDiagnostics;
+use crate::mailbox::Mailbox;
+use crate::message::{AnyMessage, MessageEnvelope, Pid, SystemMessage};
+use crate::process::Process;
 use crate::system::ActorSystem;
 use std::sync::Arc;
 
 /// A [Process] that holds reference to Actor Mailbox
 pub struct ActorProcess {
     system: Arc<ActorSystem>,
+    mailbox: Mailbox,
+    actor_type: &'static str,
+    parent: Option<Pid>,
+}
+
+impl ActorProcess {
+    pub(crate) fn new(
+        system: Arc<ActorSystem>,
+        mailbox: Mailbox,
+        actor_type: &'static str,
+        parent: Option<Pid>,
+    ) -> Self {
+        Self {
+            system,
+            mailbox,
+            actor_type,
+            parent,
+        }
+    }
+
+    /// Number of user messages waiting in the actor mailbox.
+    #[inline]
+    pub fn mailbox_len(&self) -> usize {
+        self.mailbox.len()
+    }
 }
 
 impl Process for ActorProcess {
@@ -14,17 +41,23 @@ impl Process for ActorProcess {
         self.system.clone()
     }
 
-    fn send_system_message(&self, _pid: &Pid, _msg: SystemMessage) {
-        todo!()
+    fn send_user_message(&self, pid: &Pid, envelope: MessageEnvelope<AnyMessage>) {
+        if let Err(envelope) = self.mailbox.post_user_message(envelope) {
+            self.system.dead_letter().send_user_message(pid, envelope);
+        }
+    }
+
+    fn send_system_message(&self, pid: &Pid, msg: SystemMessage) {
+        if let Err(msg) = self.mailbox.post_system_message(msg) {
+            self.system.dead_letter().send_system_message(pid, msg);
+        }
     }
-}
 
-impl ReceiverProcess for ActorProcess {
-    fn send_user_message<M>(&self, _pid: &Pid, _msg: M)
-    where
-        M: Message + Sync + 'static,
-        M::Result: Sync + 'static,
-    {
-        todo!()
+    fn get_diagnostics(&self, pid: &Pid) -> ProcessDiagnostics {
+        ProcessDiagnostics {
+            mailbox_length: Some(self.mailbox_len()),
+            parent: self.parent.clone(),
+            ..ProcessDiagnostics::new(pid.clone(), self.actor_type)
+        }
     }
 }
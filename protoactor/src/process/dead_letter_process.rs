@@ -0,0 +1,87 @@
+use crate::diagnostics::DiagnosticsTypeName;
+use crate::message::{
+    AnyMessage, DeadLetterResponse, MessageEnvelope, Pid, SystemMessage, Terminated,
+    TerminatedReason,
+};
+use crate::process::Process;
+use crate::system::ActorSystem;
+use log::info;
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Instant;
+
+/// Receives every message sent to a [Pid] that does not resolve to a live process.
+///
+/// Senders of dead messages receive [DeadLetterResponse], watchers of dead processes receive
+/// [Terminated] with [TerminatedReason::NotFound].
+pub struct DeadLetterProcess {
+    system: Weak<ActorSystem>,
+    throttle: Mutex<(Instant, i32)>,
+}
+
+impl DeadLetterProcess {
+    pub(crate) fn new(system: Weak<ActorSystem>) -> Self {
+        Self {
+            system,
+            throttle: Mutex::new((Instant::now(), 0)),
+        }
+    }
+
+    /// Returns `true` if dead letter should be logged considering throttling configuration.
+    fn should_log(&self, system: &ActorSystem) -> bool {
+        let config = system.config();
+        let mut throttle = self.throttle.lock().unwrap();
+        if throttle.0.elapsed() > config.dead_letter_throttle_interval {
+            *throttle = (Instant::now(), 0);
+        }
+        throttle.1 += 1;
+        throttle.1 <= config.dead_letter_throttle_count
+    }
+}
+
+impl Process for DeadLetterProcess {
+    fn system(&self) -> Arc<ActorSystem> {
+        self.system
+            .upgrade()
+            .expect("dead letter process outlived its actor system")
+    }
+
+    fn send_user_message(&self, pid: &Pid, envelope: MessageEnvelope<AnyMessage>) {
+        let system = self.system();
+        if let Some(metrics) = system.metrics() {
+            metrics.dead_letter(envelope.get_message().get_type_name());
+        }
+        let is_response = envelope.get_message().is::<DeadLetterResponse>();
+        if self.should_log(&system)
+            && (!is_response || system.config().dead_letter_response_logging)
+        {
+            info!(
+                "Dead letter {} to {}, sender {:?}",
+                envelope.get_message().type_name(),
+                pid,
+                envelope.get_sender()
+            );
+        }
+        if let (Some(sender), false) = (envelope.get_sender(), is_response) {
+            let response = DeadLetterResponse {
+                target: Some(pid.clone()),
+            };
+            system
+                .get_process(sender)
+                .send_user_message(sender, MessageEnvelope::wrap(AnyMessage::new(response)));
+        }
+    }
+
+    fn send_system_message(&self, pid: &Pid, msg: SystemMessage) {
+        if let SystemMessage::Watch(watch) = msg {
+            if let Some(watcher) = watch.watcher {
+                let terminated = Terminated {
+                    who: Some(pid.clone()),
+                    why: TerminatedReason::NotFound as i32,
+                };
+                self.system()
+                    .get_process(&watcher)
+                    .send_system_message(&watcher, SystemMessage::Terminated(terminated));
+            }
+        }
+    }
+}
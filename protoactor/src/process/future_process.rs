@@ -0,0 +1,143 @@
+use crate::message::{AnyMessage, DeadLetterResponse, MessageEnvelope, Pid, SystemMessage};
+use crate::process::Process;
+use crate::system::ActorSystem;
+use futures::future::BoxFuture;
+use log::info;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::oneshot;
+
+/// Future returned by request operations, resolved with the typed response.
+pub type RequestFuture<T> = BoxFuture<'static, Result<T, RequestError>>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RequestError {
+    /// No response arrived within the request timeout.
+    Timeout,
+    /// Request target does not exist.
+    DeadLetter(Option<Pid>),
+    /// Response arrived, but it is not of the expected type. Holds the response type name.
+    UnexpectedResponse(&'static str),
+    /// Request does not fit in a frame of the remote transport.
+    #[cfg(feature = "remote")]
+    MessageTooLarge { size: usize, max_size: usize },
+    /// Address of the request target is blocked or quarantined.
+    #[cfg(feature = "remote")]
+    Blocked(crate::remote::BlockReason),
+}
+
+impl Display for RequestError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RequestError::Timeout => write!(f, "request timed out"),
+            RequestError::DeadLetter(Some(target)) => write!(f, "{} is a dead letter", target),
+            RequestError::DeadLetter(None) => write!(f, "request target is a dead letter"),
+            RequestError::UnexpectedResponse(type_name) => {
+                write!(f, "unexpected response type {}", type_name)
+            }
+            #[cfg(feature = "remote")]
+            RequestError::MessageTooLarge { size, max_size } => write!(
+                f,
+                "request of {} bytes exceeds the maximum frame size of {}",
+                size, max_size
+            ),
+            #[cfg(feature = "remote")]
+            RequestError::Blocked(reason) => write!(f, "request target address is {}", reason),
+        }
+    }
+}
+
+impl Error for RequestError {}
+
+/// Temporary [Process] that completes a request with the first message it receives.
+pub struct FutureProcess {
+    system: Arc<ActorSystem>,
+    sender: Mutex<Option<oneshot::Sender<AnyMessage>>>,
+}
+
+impl FutureProcess {
+    /// Registers a new future process and returns its [Pid] together with the future that
+    /// resolves with the response, or fails after `timeout`.
+    pub fn spawn<R>(system: &Arc<ActorSystem>, timeout: Duration) -> (Pid, RequestFuture<R>)
+    where
+        R: Send + Sync + 'static,
+    {
+        let (tx, rx) = oneshot::channel();
+        let process = Arc::new(Self {
+            system: system.clone(),
+            sender: Mutex::new(Some(tx)),
+        });
+        let id = system.registry().next_id();
+        let pid = system
+            .registry()
+            .add(id, process)
+            .expect("sequence ids are unique");
+
+        let system = system.clone();
+        let future_pid = pid.clone();
+        let future = Box::pin(async move {
+            let response = tokio::time::timeout(timeout, rx).await;
+            system.registry().remove(&future_pid);
+            match response {
+                Ok(Ok(response)) => Self::downcast_response(&system, response),
+                Ok(Err(_)) => Err(RequestError::Timeout),
+                Err(_) => {
+                    if let Some(metrics) = system.metrics() {
+                        metrics.future_timed_out();
+                    }
+                    Err(RequestError::Timeout)
+                }
+            }
+        });
+        (pid, future)
+    }
+
+    fn downcast_response<R>(system: &ActorSystem, response: AnyMessage) -> Result<R, RequestError>
+    where
+        R: Send + Sync + 'static,
+    {
+        if let Some(dead_letter) = response.downcast_ref::<DeadLetterResponse>() {
+            if system.config().dead_letter_request_logging {
+                info!("Request to dead letter {:?}", dead_letter.target);
+            }
+            return Err(RequestError::DeadLetter(dead_letter.target.clone()));
+        }
+        #[cfg(feature = "remote")]
+        if let Some(too_large) = response.downcast_ref::<crate::remote::MessageTooLarge>() {
+            return Err(RequestError::MessageTooLarge {
+                size: too_large.size,
+                max_size: too_large.max_size,
+            });
+        }
+        #[cfg(feature = "remote")]
+        if let Some(blocked) = response.downcast_ref::<crate::remote::MessageBlocked>() {
+            return Err(RequestError::Blocked(blocked.reason));
+        }
+        let type_name = response.type_name();
+        response
+            .try_unwrap::<R>()
+            .map_err(|_| RequestError::UnexpectedResponse(type_name))
+    }
+}
+
+impl Process for FutureProcess {
+    #[inline]
+    fn system(&self) -> Arc<ActorSystem> {
+        self.system.clone()
+    }
+
+    fn send_user_message(&self, _pid: &Pid, envelope: MessageEnvelope<AnyMessage>) {
+        if let Some(sender) = self.sender.lock().unwrap().take() {
+            let _ = sender.send(envelope.into_message());
+        }
+    }
+
+    fn send_system_message(&self, pid: &Pid, msg: SystemMessage) {
+        if let SystemMessage::Stop = msg {
+            self.sender.lock().unwrap().take();
+            self.system.registry().remove(pid);
+        }
+    }
+}
@@ -1,16 +1,208 @@
 use crate::message::Pid;
-use std::sync::Arc;
+use chashmap::CHashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, RwLock};
 
-use crate::process::Process;
-use std::sync::atomic::AtomicU64;
+use crate::process::{Process, SpawningProcess};
+use crate::system::NO_HOST;
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Resolves [Process] for [Pid] that belongs to some other actor system.
+pub type HostResolver = Box<dyn Fn(&Pid) -> Option<Arc<dyn Process>> + Send + Sync>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpawnError {
+    /// Process with the same id is already registered.
+    NameExists(Pid),
+}
+
+impl Display for SpawnError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SpawnError::NameExists(pid) => write!(f, "process {} already exists", pid),
+        }
+    }
+}
+
+impl Error for SpawnError {}
+
+thread_local! {
+    /// Name reserved by a spawning process that the current thread registers an actor under,
+    /// see [Registry::claim].
+    static CLAIM: RefCell<Option<(String, Arc<SpawningProcess>)>> = const { RefCell::new(None) };
+}
 
 /// Manages all processes in the actor system (actors, futures, event stream, etc.).
 pub struct Registry {
+    address: RwLock<String>,
     sequence_id: AtomicU64,
-    host_resolvers: Vec<Box<dyn Fn(&Pid) -> Arc<dyn Process>>>,
+    host_resolvers: RwLock<Vec<HostResolver>>,
+    local_processes: CHashMap<String, Arc<dyn Process>>,
 }
 
-impl Registry {}
+impl Registry {
+    pub(crate) fn new(address: String) -> Self {
+        Self {
+            address: RwLock::new(address),
+            sequence_id: AtomicU64::new(0),
+            host_resolvers: RwLock::new(Vec::new()),
+            local_processes: CHashMap::new(),
+        }
+    }
+
+    /// Address of the actor system that owns this registry.
+    #[inline]
+    pub fn address(&self) -> String {
+        self.address.read().unwrap().clone()
+    }
+
+    pub(crate) fn set_address(&self, address: String) {
+        *self.address.write().unwrap() = address;
+    }
+
+    /// Generates unique process id.
+    pub fn next_id(&self) -> String {
+        format!("${}", self.sequence_id.fetch_add(1, Ordering::Relaxed) + 1)
+    }
+
+    /// Registers local process under `id`.
+    pub fn add(&self, id: String, process: Arc<dyn Process>) -> Result<Pid, SpawnError> {
+        let pid = Pid::new(self.address().as_str(), id.as_str());
+        let reserved = CLAIM.with(|claim| {
+            let mut claim = claim.borrow_mut();
+            match &*claim {
+                Some((claimed, _)) if *claimed == id => claim.take().map(|(_, reserved)| reserved),
+                _ => None,
+            }
+        });
+        if let Some(reserved) = &reserved {
+            reserved.spawned(process.clone());
+        }
+        let mut exists = false;
+        self.local_processes.alter(id, |existing| {
+            exists = existing.is_some() && reserved.is_none();
+            match exists {
+                true => existing,
+                false => Some(process),
+            }
+        });
+        if exists {
+            Err(SpawnError::NameExists(pid))
+        } else {
+            Ok(pid)
+        }
+    }
+
+    /// Runs `spawn`, letting the process it registers under `id` replace the `reserved`
+    /// process, which passes the messages it kept on to it.
+    pub(crate) fn claim<F, R>(&self, id: &str, reserved: &Arc<SpawningProcess>, spawn: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        CLAIM.with(|claim| *claim.borrow_mut() = Some((id.to_string(), reserved.clone())));
+        let result = spawn();
+        CLAIM.with(|claim| claim.borrow_mut().take());
+        result
+    }
+
+    pub fn remove(&self, pid: &Pid) {
+        self.local_processes.remove(&pid.id);
+    }
+
+    /// Finds process of the [Pid], if it is still alive.
+    pub fn get(&self, pid: &Pid) -> Option<Arc<dyn Process>> {
+        if self.is_local(pid) {
+            return self
+                .local_processes
+                .get(&pid.id)
+                .map(|process| process.clone());
+        }
+        self.host_resolvers
+            .read()
+            .unwrap()
+            .iter()
+            .find_map(|resolve| resolve(pid))
+    }
+
+    /// Registers resolver used to find processes of [Pid]s with non-local address.
+    pub fn register_host_resolver(&self, resolver: HostResolver) {
+        self.host_resolvers.write().unwrap().push(resolver);
+    }
+
+    /// Returns `true` if [Pid] belongs to this actor system, including [Pid]s created before
+    /// the actor system got its network address.
+    #[inline]
+    pub fn is_local(&self, pid: &Pid) -> bool {
+        pid.address == NO_HOST || pid.address == *self.address.read().unwrap()
+    }
+
+    /// Ids of all registered local processes.
+    pub fn process_ids(&self) -> Vec<String> {
+        self.local_processes
+            .clone()
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect()
+    }
+
+    /// All registered local processes.
+    pub fn processes(&self) -> Vec<(Pid, Arc<dyn Process>)> {
+        let address = self.address();
+        self.local_processes
+            .clone()
+            .into_iter()
+            .map(|(id, process)| (Pid::new(address.as_str(), id.as_str()), process))
+            .collect()
+    }
+}
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use crate::message::{Pid, SystemMessage};
+    use crate::process::{Registry, SpawnError};
+    use crate::system::ActorSystem;
+
+    #[test]
+    fn should_generate_unique_ids() {
+        let registry = Registry::new("nohost".into());
+
+        assert_eq!("$1", registry.next_id());
+        assert_eq!("$2", registry.next_id());
+    }
+
+    #[tokio::test]
+    async fn should_reject_duplicate_names() {
+        let system = ActorSystem::new();
+        let registry = system.registry();
+        let process = system.dead_letter();
+
+        let pid = registry.add("name".into(), process.clone()).unwrap();
+
+        assert_eq!(Pid::new("nohost", "name"), pid);
+        assert_eq!(
+            Err(SpawnError::NameExists(pid.clone())),
+            registry.add("name".into(), process)
+        );
+        registry.remove(&pid);
+        assert!(registry.get(&pid).is_none());
+    }
+
+    #[tokio::test]
+    async fn should_resolve_remote_pids_through_host_resolvers() {
+        let system = ActorSystem::new();
+        let dead_letter = system.dead_letter();
+        system
+            .registry()
+            .register_host_resolver(Box::new(move |pid| {
+                (pid.address == "remote:1").then(|| dead_letter.clone())
+            }));
+
+        assert!(system.registry().get(&Pid::new("remote:1", "a")).is_some());
+        assert!(system.registry().get(&Pid::new("remote:2", "a")).is_none());
+        system
+            .get_process(&Pid::new("remote:2", "a"))
+            .send_system_message(&Pid::new("remote:2", "a"), SystemMessage::Stop);
+    }
+}
@@ -0,0 +1,117 @@
+use crate::message::{
+    AnyMessage, MessageEnvelope, Pid, SystemMessage, Terminated, TerminatedReason,
+};
+use crate::process::Process;
+use crate::system::ActorSystem;
+use std::mem;
+use std::sync::{Arc, Mutex, Weak};
+
+/// Holds the name of an actor whose spawn pipeline has not completed yet.
+///
+/// Messages sent to it are kept until the actor registers under the name, see
+/// [Registry::claim](crate::process::Registry::claim), and passed on to it in the order they
+/// were sent. If the pipeline spawns no actor under the name, they go to dead letters.
+pub(crate) struct SpawningProcess {
+    system: Weak<ActorSystem>,
+    state: Mutex<SpawningState>,
+}
+
+enum SpawningState {
+    Pending(Vec<Pending>),
+    Spawned(Arc<dyn Process>),
+    Failed,
+}
+
+enum Pending {
+    User(Pid, MessageEnvelope<AnyMessage>),
+    System(Pid, SystemMessage),
+}
+
+impl SpawningProcess {
+    pub(crate) fn new(system: &Arc<ActorSystem>) -> Self {
+        Self {
+            system: Arc::downgrade(system),
+            state: Mutex::new(SpawningState::Pending(Vec::new())),
+        }
+    }
+
+    /// Passes kept and later messages on to the spawned actor process.
+    pub(crate) fn spawned(&self, process: Arc<dyn Process>) {
+        let mut state = self.state.lock().unwrap();
+        let SpawningState::Pending(pending) = &mut *state else {
+            return;
+        };
+        for message in mem::take(pending) {
+            match message {
+                Pending::User(pid, envelope) => process.send_user_message(&pid, envelope),
+                Pending::System(pid, message) => process.send_system_message(&pid, message),
+            }
+        }
+        *state = SpawningState::Spawned(process);
+    }
+
+    /// Unregisters the name if no actor was spawned under it, sends kept messages to dead
+    /// letters and tells the `parent` the actor is gone.
+    pub(crate) fn complete(&self, pid: &Pid, parent: Option<&Pid>) {
+        let Some(system) = self.system.upgrade() else {
+            return;
+        };
+        let mut state = self.state.lock().unwrap();
+        let SpawningState::Pending(pending) = mem::replace(&mut *state, SpawningState::Failed)
+        else {
+            return;
+        };
+        system.registry().remove(pid);
+        drop(state);
+        let dead_letter = system.dead_letter();
+        for message in pending {
+            match message {
+                Pending::User(pid, envelope) => dead_letter.send_user_message(&pid, envelope),
+                Pending::System(pid, message) => dead_letter.send_system_message(&pid, message),
+            }
+        }
+        if let Some(parent) = parent {
+            let terminated = Terminated {
+                who: Some(pid.clone()),
+                why: TerminatedReason::NotFound as i32,
+            };
+            system
+                .get_process(parent)
+                .send_system_message(parent, SystemMessage::Terminated(terminated));
+        }
+    }
+}
+
+impl Process for SpawningProcess {
+    fn system(&self) -> Arc<ActorSystem> {
+        self.system
+            .upgrade()
+            .expect("spawning process outlived its actor system")
+    }
+
+    fn send_user_message(&self, pid: &Pid, envelope: MessageEnvelope<AnyMessage>) {
+        let mut state = self.state.lock().unwrap();
+        match &mut *state {
+            SpawningState::Pending(pending) => pending.push(Pending::User(pid.clone(), envelope)),
+            SpawningState::Spawned(process) => process.send_user_message(pid, envelope),
+            SpawningState::Failed => {
+                drop(state);
+                self.system().dead_letter().send_user_message(pid, envelope)
+            }
+        }
+    }
+
+    fn send_system_message(&self, pid: &Pid, message: SystemMessage) {
+        let mut state = self.state.lock().unwrap();
+        match &mut *state {
+            SpawningState::Pending(pending) => pending.push(Pending::System(pid.clone(), message)),
+            SpawningState::Spawned(process) => process.send_system_message(pid, message),
+            SpawningState::Failed => {
+                drop(state);
+                self.system()
+                    .dead_letter()
+                    .send_system_message(pid, message)
+            }
+        }
+    }
+}
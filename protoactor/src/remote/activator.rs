@@ -0,0 +1,126 @@
+use crate::actor::Props;
+use crate::message::{AnyMessage, MessageEnvelope, Pid, SystemMessage};
+use crate::process::{Process, SpawnError};
+use crate::remote::protos::{ActorPidRequest, ActorPidResponse, ResponseStatusCode};
+use crate::system::ActorSystem;
+use log::debug;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Weak};
+
+/// Id of the process that spawns actors on request of remote actor systems.
+pub(crate) const ACTIVATOR: &str = "activator";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RemoteSpawnError {
+    /// Remote actor system has no kind registered under the name.
+    UnknownKind(String),
+    /// Process with the requested name already exists on the remote actor system.
+    NameExists(Pid),
+    /// Remote actor system did not respond within the timeout.
+    Timeout,
+    /// Remote actor system could not be reached.
+    Unavailable,
+    /// Remote actor system responded with an unexpected status code.
+    Failed(i32),
+}
+
+impl Display for RemoteSpawnError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RemoteSpawnError::UnknownKind(kind) => write!(f, "unknown actor kind {}", kind),
+            RemoteSpawnError::NameExists(pid) => write!(f, "process {} already exists", pid),
+            RemoteSpawnError::Timeout => write!(f, "remote spawn timed out"),
+            RemoteSpawnError::Unavailable => write!(f, "remote actor system is unavailable"),
+            RemoteSpawnError::Failed(status_code) => {
+                write!(f, "remote spawn failed with status {}", status_code)
+            }
+        }
+    }
+}
+
+impl Error for RemoteSpawnError {}
+
+impl RemoteSpawnError {
+    pub(crate) fn from_response(kind: &str, response: ActorPidResponse) -> Result<Pid, Self> {
+        match (
+            ResponseStatusCode::from_i32(response.status_code),
+            response.pid,
+        ) {
+            (Some(ResponseStatusCode::Ok), Some(pid)) => Ok(pid),
+            (Some(ResponseStatusCode::Processnamealreadyexist), Some(pid)) => {
+                Err(Self::NameExists(pid))
+            }
+            (Some(ResponseStatusCode::Unknownkind), _) => Err(Self::UnknownKind(kind.into())),
+            (Some(ResponseStatusCode::Timeout), _) => Err(Self::Timeout),
+            (Some(ResponseStatusCode::Unavailable | ResponseStatusCode::Deadletter), _) => {
+                Err(Self::Unavailable)
+            }
+            _ => Err(Self::Failed(response.status_code)),
+        }
+    }
+}
+
+/// Spawns actors of kinds registered in [RemoteConfig](crate::remote::RemoteConfig) and
+/// responds with [ActorPidResponse].
+pub(crate) struct Activator {
+    system: Weak<ActorSystem>,
+    kinds: HashMap<String, Props>,
+}
+
+impl Activator {
+    pub(crate) fn new(system: &Arc<ActorSystem>, kinds: HashMap<String, Props>) -> Self {
+        Self {
+            system: Arc::downgrade(system),
+            kinds,
+        }
+    }
+
+    fn activate(&self, system: &Arc<ActorSystem>, request: &ActorPidRequest) -> ActorPidResponse {
+        let Some(props) = self.kinds.get(&request.kind) else {
+            return ActorPidResponse {
+                pid: None,
+                status_code: ResponseStatusCode::Unknownkind as i32,
+            };
+        };
+        let name = match request.name.as_str() {
+            "" => system.registry().next_id(),
+            name => name.to_string(),
+        };
+        match props.spawn(system, name, None) {
+            Ok(pid) => ActorPidResponse {
+                pid: Some(pid),
+                status_code: ResponseStatusCode::Ok as i32,
+            },
+            Err(SpawnError::NameExists(pid)) => ActorPidResponse {
+                pid: Some(pid),
+                status_code: ResponseStatusCode::Processnamealreadyexist as i32,
+            },
+        }
+    }
+}
+
+impl Process for Activator {
+    fn system(&self) -> Arc<ActorSystem> {
+        self.system
+            .upgrade()
+            .expect("activator outlived its actor system")
+    }
+
+    fn send_user_message(&self, _pid: &Pid, envelope: MessageEnvelope<AnyMessage>) {
+        let Some(request) = envelope.get_message().downcast_ref::<ActorPidRequest>() else {
+            return debug!("Activator ignored {:?}", envelope.get_message());
+        };
+        let system = self.system();
+        let response = self.activate(&system, request);
+        debug!("Activated {} as {:?}", request.kind, response.pid);
+        if let Some(sender) = envelope.get_sender() {
+            system
+                .get_process(sender)
+                .send_user_message(sender, MessageEnvelope::wrap(AnyMessage::new(response)));
+        }
+    }
+
+    fn send_system_message(&self, _pid: &Pid, _msg: SystemMessage) {}
+}
@@ -0,0 +1,245 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Why messages to and from a remote address are dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockReason {
+    /// Blocked with [Remote::block](crate::remote::Remote::block).
+    Blocked,
+    /// Quarantined after sending too many messages that violate the remote protocol.
+    Quarantined { protocol_errors: u32 },
+}
+
+impl Display for BlockReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlockReason::Blocked => write!(f, "blocked"),
+            BlockReason::Quarantined { protocol_errors } => {
+                write!(f, "quarantined after {} protocol errors", protocol_errors)
+            }
+        }
+    }
+}
+
+struct Block {
+    reason: BlockReason,
+    until: Option<Instant>,
+}
+
+/// Remote addresses no messages are exchanged with, and protocol errors of the others.
+///
+/// Blocks are kept by address. Peers authenticated with a certificate are blocked by their
+/// identity along with the address they announce, so they cannot get around a block by
+/// announcing another address.
+#[derive(Default)]
+pub(crate) struct Blocklist {
+    blocks: Mutex<HashMap<String, Block>>,
+    /// Identities blocked along with an address, lifted when it is unblocked.
+    identities: Mutex<HashMap<String, HashSet<String>>>,
+    /// Number of connections of peer identities, by the address they announced.
+    connected: Mutex<HashMap<String, HashMap<String, usize>>>,
+    /// Start of the counting window and protocol errors counted in it, by address or identity.
+    protocol_errors: Mutex<HashMap<String, (Instant, u32)>>,
+}
+
+impl Blocklist {
+    /// Blocks the address until unblocked, or for the `duration`, together with the
+    /// `identity` and the identities of peers connected under the address.
+    pub(crate) fn block(
+        &self,
+        address: &str,
+        identity: Option<&str>,
+        reason: BlockReason,
+        duration: Option<Duration>,
+    ) {
+        let until = duration.map(|duration| Instant::now() + duration);
+        let mut identities = self
+            .connected
+            .lock()
+            .unwrap()
+            .get(address)
+            .map(|connected| connected.keys().cloned().collect::<HashSet<_>>())
+            .unwrap_or_default();
+        identities.extend(identity.map(str::to_string));
+        let mut blocks = self.blocks.lock().unwrap();
+        blocks.insert(address.to_string(), Block { reason, until });
+        self.block_identities(&mut blocks, address, identities, reason, until);
+    }
+
+    fn block_identities(
+        &self,
+        blocks: &mut HashMap<String, Block>,
+        address: &str,
+        identities: HashSet<String>,
+        reason: BlockReason,
+        until: Option<Instant>,
+    ) {
+        if identities.is_empty() {
+            return;
+        }
+        for identity in &identities {
+            blocks.insert(identity.clone(), Block { reason, until });
+        }
+        self.identities
+            .lock()
+            .unwrap()
+            .entry(address.to_string())
+            .or_default()
+            .extend(identities);
+    }
+
+    /// Lifts blocks of the address and of the identities blocked along with it.
+    pub(crate) fn unblock(&self, address: &str) {
+        let identities = self
+            .identities
+            .lock()
+            .unwrap()
+            .remove(address)
+            .unwrap_or_default();
+        let mut blocks = self.blocks.lock().unwrap();
+        let mut protocol_errors = self.protocol_errors.lock().unwrap();
+        for key in identities.iter().map(String::as_str).chain([address]) {
+            blocks.remove(key);
+            protocol_errors.remove(key);
+        }
+    }
+
+    /// Reason the address is blocked for, or [None] if it is not blocked.
+    pub(crate) fn get(&self, address: &str) -> Option<BlockReason> {
+        get_block(&mut self.blocks.lock().unwrap(), address)
+    }
+
+    /// Reason the peer announcing the `address` and authenticated with the `identity` is
+    /// blocked for. The identity of a peer announcing a blocked address is blocked as well.
+    pub(crate) fn get_peer(&self, address: &str, identity: Option<&str>) -> Option<BlockReason> {
+        let mut blocks = self.blocks.lock().unwrap();
+        if let Some(reason) = identity.and_then(|identity| get_block(&mut blocks, identity)) {
+            return Some(reason);
+        }
+        get_block(&mut blocks, address)?;
+        let block = &blocks[address];
+        let (reason, until) = (block.reason, block.until);
+        let identities = identity.map(str::to_string).into_iter().collect();
+        self.block_identities(&mut blocks, address, identities, reason, until);
+        Some(reason)
+    }
+
+    /// Keeps track of the identity connected under the address until [Self::disconnect].
+    pub(crate) fn connect(&self, address: &str, identity: &str) {
+        let mut connected = self.connected.lock().unwrap();
+        let identities = connected.entry(address.to_string()).or_default();
+        *identities.entry(identity.to_string()).or_default() += 1;
+    }
+
+    pub(crate) fn disconnect(&self, address: &str, identity: &str) {
+        let mut connected = self.connected.lock().unwrap();
+        let Some(identities) = connected.get_mut(address) else {
+            return;
+        };
+        if let Some(count) = identities.get_mut(identity) {
+            *count -= 1;
+            if *count == 0 {
+                identities.remove(identity);
+            }
+        }
+        if identities.is_empty() {
+            connected.remove(address);
+        }
+    }
+
+    /// Counts protocol errors of the address or identity within the `window`, returns their
+    /// number once it reaches `max_errors`.
+    pub(crate) fn protocol_errors(
+        &self,
+        key: &str,
+        count: u32,
+        max_errors: u32,
+        window: Duration,
+    ) -> Option<u32> {
+        let mut protocol_errors = self.protocol_errors.lock().unwrap();
+        let errors = protocol_errors
+            .entry(key.to_string())
+            .or_insert_with(|| (Instant::now(), 0));
+        if errors.0.elapsed() > window {
+            *errors = (Instant::now(), 0);
+        }
+        errors.1 += count;
+        let errors = errors.1;
+        (errors >= max_errors).then(|| {
+            protocol_errors.remove(key);
+            errors
+        })
+    }
+}
+
+/// Removes the block of the key once it expired.
+fn get_block(blocks: &mut HashMap<String, Block>, key: &str) -> Option<BlockReason> {
+    let block = blocks.get(key)?;
+    if block.until.is_some_and(|until| until <= Instant::now()) {
+        blocks.remove(key);
+        return None;
+    }
+    Some(block.reason)
+}
+
+/// Identity of a peer authenticated with certificates, derived from its own certificate.
+pub(crate) fn identity_of(certificates: &[Vec<u8>]) -> Option<String> {
+    let certificate = certificates.first()?;
+    let hex = certificate.iter().map(|byte| format!("{:02x}", byte));
+    Some(format!("certificate:{}", hex.collect::<String>()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::remote::blocklist::{BlockReason, Blocklist};
+    use std::time::Duration;
+
+    #[test]
+    fn should_expire_quarantine() {
+        let blocklist = Blocklist::default();
+        let window = Duration::from_secs(60);
+        assert_eq!(None, blocklist.protocol_errors("a:1", 2, 3, window));
+        assert_eq!(Some(4), blocklist.protocol_errors("a:1", 2, 3, window));
+        assert_eq!(None, blocklist.protocol_errors("a:1", 1, 3, window));
+
+        let quarantined = BlockReason::Quarantined { protocol_errors: 4 };
+        blocklist.block("a:1", None, quarantined, Some(Duration::ZERO));
+        blocklist.block("b:1", None, BlockReason::Blocked, None);
+
+        assert_eq!(None, blocklist.get("a:1"));
+        assert_eq!(Some(BlockReason::Blocked), blocklist.get("b:1"));
+        blocklist.unblock("b:1");
+        assert_eq!(None, blocklist.get("b:1"));
+    }
+
+    #[test]
+    fn should_block_identity_of_peers_announcing_blocked_address() {
+        let blocklist = Blocklist::default();
+        blocklist.connect("10.0.0.1:4000", "a");
+        blocklist.block("10.0.0.1:4000", None, BlockReason::Blocked, None);
+        let quarantined = BlockReason::Quarantined { protocol_errors: 3 };
+        blocklist.block("10.0.0.2:4000", None, quarantined, None);
+        blocklist.disconnect("10.0.0.1:4000", "a");
+
+        let blocked = Some(BlockReason::Blocked);
+        assert_eq!(blocked, blocklist.get_peer("10.0.0.1:5000", Some("a")));
+        assert_eq!(None, blocklist.get("10.0.0.1:5000"));
+        assert_eq!(None, blocklist.get_peer("10.0.0.1:5000", None));
+        assert_eq!(
+            Some(quarantined),
+            blocklist.get_peer("10.0.0.2:4000", Some("b"))
+        );
+        assert_eq!(
+            Some(quarantined),
+            blocklist.get_peer("10.0.0.3:4000", Some("b"))
+        );
+        assert_eq!(None, blocklist.get_peer("10.0.0.3:4000", Some("c")));
+
+        blocklist.unblock("10.0.0.1:4000");
+        blocklist.unblock("10.0.0.2:4000");
+        assert_eq!(None, blocklist.get_peer("10.0.0.1:5000", Some("a")));
+        assert_eq!(None, blocklist.get_peer("10.0.0.3:4000", Some("b")));
+    }
+}
@@ -0,0 +1,169 @@
+#[cfg(any(feature = "zstd", feature = "lz4"))]
+use crate::remote::frame::check_frame_size;
+use std::io;
+
+/// Compression of message batches sent to a remote actor system.
+///
+/// Actor systems offer the algorithms of their [RemoteConfig](crate::remote::RemoteConfig)
+/// when connecting, the accepting side picks the first one it has configured too. Batches are
+/// sent uncompressed if there is none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// Zstandard with the compression level, `0` being the default level.
+    #[cfg(feature = "zstd")]
+    Zstd(i32),
+    #[cfg(feature = "lz4")]
+    Lz4,
+}
+
+#[cfg_attr(not(any(feature = "zstd", feature = "lz4")), allow(unused_variables))]
+impl Compression {
+    /// Name of the algorithm on the wire.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            #[cfg(feature = "zstd")]
+            Compression::Zstd(_) => "zstd",
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => "lz4",
+        }
+    }
+
+    pub(crate) fn compress(&self, data: &[u8]) -> io::Result<Vec<u8>> {
+        match *self {
+            #[cfg(feature = "zstd")]
+            Compression::Zstd(level) => zstd::bulk::compress(data, level),
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => Ok(lz4_flex::compress_prepend_size(data)),
+        }
+    }
+
+    /// Decompresses the data, failing with [io::ErrorKind::InvalidData] if it would grow over
+    /// `max_size` bytes.
+    pub(crate) fn decompress(&self, data: &[u8], max_size: usize) -> io::Result<Vec<u8>> {
+        match *self {
+            #[cfg(feature = "zstd")]
+            Compression::Zstd(_) => {
+                use std::io::Read;
+                let mut decompressed = Vec::new();
+                zstd::stream::read::Decoder::new(data)?
+                    .take(max_size as u64 + 1)
+                    .read_to_end(&mut decompressed)?;
+                check_frame_size(decompressed.len() as u64, max_size)?;
+                Ok(decompressed)
+            }
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => {
+                let size = data.get(..4).map_or(0, |size| {
+                    u32::from_le_bytes([size[0], size[1], size[2], size[3]])
+                });
+                check_frame_size(size as u64, max_size)?;
+                lz4_flex::decompress_size_prepended(data)
+                    .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+            }
+        }
+    }
+
+    /// First of the `offered` algorithm names that is `supported`.
+    pub(crate) fn negotiate<S>(offered: &[S], supported: &[Compression]) -> Option<Compression>
+    where
+        S: AsRef<str>,
+    {
+        offered.iter().find_map(|name| {
+            supported
+                .iter()
+                .find(|compression| compression.name() == name.as_ref())
+                .copied()
+        })
+    }
+}
+
+#[cfg(all(test, feature = "zstd", feature = "lz4"))]
+mod tests {
+    use crate::actor::Props;
+    use crate::context::SenderContext;
+    use crate::remote::endpoint::batch_frame;
+    use crate::remote::protos::{self, remote_message, MessageBatch};
+    use crate::remote::tests::{config, new_system, Echo, Ping};
+    use crate::remote::{Compression, Remote};
+    use rand::RngCore;
+    use std::time::Duration;
+
+    #[test]
+    fn should_negotiate_first_offered_supported_compression() {
+        let supported = [Compression::Lz4, Compression::Zstd(3)];
+
+        let negotiated = Compression::negotiate(&["gzip", "zstd", "lz4"], &supported);
+
+        assert_eq!(Some(Compression::Zstd(3)), negotiated);
+        assert_eq!(None, Compression::negotiate(&["gzip"], &supported));
+        assert_eq!(None, Compression::negotiate(&["lz4"], &[]));
+    }
+
+    #[test]
+    fn should_round_trip_compressed_data() {
+        let data = "ping ".repeat(100).into_bytes();
+        for compression in [Compression::Zstd(0), Compression::Lz4] {
+            let compressed = compression.compress(&data).unwrap();
+            assert!(compressed.len() < data.len());
+            assert_eq!(data, compression.decompress(&compressed, 500).unwrap());
+            let error = compression.decompress(&compressed, 499).unwrap_err();
+            assert_eq!(std::io::ErrorKind::InvalidData, error.kind());
+        }
+    }
+
+    #[test]
+    fn should_send_batches_uncompressed_unless_compression_makes_them_smaller() {
+        let batch = |message_data: Vec<u8>| MessageBatch {
+            envelopes: vec![protos::MessageEnvelope {
+                message_data,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mut random = vec![0; 1000];
+        rand::thread_rng().fill_bytes(&mut random);
+        let repeated = "ping ".repeat(200).into_bytes();
+
+        for compression in [Compression::Zstd(0), Compression::Lz4] {
+            let frame = batch_frame(batch(random.clone()), Some(compression)).unwrap();
+            let expected = remote_message::MessageType::MessageBatch(batch(random.clone()));
+            assert_eq!(Some(expected), frame.message_type);
+            let frame = batch_frame(batch(repeated.clone()), Some(compression)).unwrap();
+            assert!(matches!(
+                frame.message_type,
+                Some(remote_message::MessageType::CompressedBatch(_))
+            ));
+        }
+    }
+
+    #[tokio::test]
+    async fn should_exchange_batches_with_and_without_compression() {
+        let system_a = new_system();
+        let config_a = config(0)
+            .with_compression(Compression::Zstd(0))
+            .with_compression(Compression::Lz4);
+        let _remote_a = Remote::start(&system_a, config_a).await.unwrap();
+        let echo = system_a.root().spawn(Props::from_producer(|| Echo));
+        let config_b = config(0)
+            .with_compression(Compression::Lz4)
+            .with_batch_size(10)
+            .with_batch_linger(Duration::from_millis(5));
+        let config_c = config(0);
+
+        for config in [config_b, config_c] {
+            let system = new_system();
+            let _remote = Remote::start(&system, config).await.unwrap();
+            let requests = (0..25).map(|index| {
+                let ping = Ping {
+                    text: format!("ping {}", index),
+                };
+                system.root().request_future(&echo, ping)
+            });
+            let pongs = futures::future::join_all(requests).await;
+            for (index, pong) in pongs.into_iter().enumerate() {
+                let prefix = format!("ping {} ", index);
+                assert!(pong.unwrap().text.starts_with(&prefix));
+            }
+        }
+    }
+}
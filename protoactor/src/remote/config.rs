@@ -0,0 +1,298 @@
+use crate::actor::Props;
+use crate::message::Message;
+use crate::remote::{Compression, Peer, Serializer, SerializerRegistry, TcpTransport, Transport};
+#[cfg(unix)]
+use crate::remote::{UnixTransport, UNIX_SCHEME};
+use rand::Rng;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::collections::HashMap;
+#[cfg(unix)]
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Default limit of frames exchanged with remote actor systems, the default maximum message
+/// size of gRPC.
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 4 * 1024 * 1024;
+
+/// Configuration of [Remote](crate::remote::Remote).
+#[derive(Clone)]
+pub struct RemoteConfig {
+    pub(crate) host: String,
+    pub(crate) port: u16,
+    pub(crate) advertised_host: Option<String>,
+    pub(crate) batch_size: usize,
+    pub(crate) batch_linger: Duration,
+    pub(crate) compressions: Vec<Compression>,
+    pub(crate) connect_timeout: Duration,
+    pub(crate) heartbeat_interval: Duration,
+    pub(crate) heartbeat_timeout: Duration,
+    pub(crate) initial_backoff: Duration,
+    pub(crate) max_backoff: Duration,
+    pub(crate) max_reconnect_attempts: u32,
+    pub(crate) max_buffered_messages: usize,
+    pub(crate) max_frame_size: usize,
+    pub(crate) max_protocol_errors: u32,
+    pub(crate) quarantine_duration: Duration,
+    pub(crate) serializers: SerializerRegistry,
+    pub(crate) kinds: HashMap<String, Props>,
+    pub(crate) transport: Arc<dyn Transport>,
+    pub(crate) authorizer: Option<Arc<PeerAuthorizer>>,
+}
+
+/// Decides whether a [Peer] may send messages to the local actor system.
+pub type PeerAuthorizer = dyn Fn(&Peer) -> bool + Send + Sync;
+
+impl RemoteConfig {
+    /// Listens on `host` and `port`, port `0` picks a free port.
+    pub fn bind<H>(host: H, port: u16) -> Self
+    where
+        H: Into<String>,
+    {
+        Self {
+            host: host.into(),
+            port,
+            advertised_host: None,
+            batch_size: 1000,
+            batch_linger: Duration::ZERO,
+            compressions: Vec::new(),
+            connect_timeout: Duration::from_secs(5),
+            heartbeat_interval: Duration::from_secs(1),
+            heartbeat_timeout: Duration::from_secs(5),
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            max_reconnect_attempts: 10,
+            max_buffered_messages: 10_000,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            max_protocol_errors: 10,
+            quarantine_duration: Duration::from_secs(60),
+            serializers: SerializerRegistry::default(),
+            kinds: HashMap::new(),
+            transport: Arc::new(TcpTransport),
+            authorizer: None,
+        }
+    }
+
+    /// Host other actor systems connect to, when it differs from the one listened on, e.g.
+    /// when listening on `0.0.0.0`.
+    pub fn with_advertised_host<H>(self, host: H) -> Self
+    where
+        H: Into<String>,
+    {
+        Self {
+            advertised_host: Some(host.into()),
+            ..self
+        }
+    }
+
+    /// Listens on a Unix domain socket at `path` using [UnixTransport], the actor system is
+    /// reachable at `unix:{path}` from actor systems on the same host.
+    #[cfg(unix)]
+    pub fn bind_unix<P>(path: P) -> Self
+    where
+        P: AsRef<Path>,
+    {
+        let address = format!("{}{}", UNIX_SCHEME, path.as_ref().display());
+        Self::bind(address, 0).with_transport(UnixTransport)
+    }
+
+    /// Transport connecting actor systems, [TcpTransport] by default.
+    pub fn with_transport<T>(self, transport: T) -> Self
+    where
+        T: Transport + 'static,
+    {
+        Self {
+            transport: Arc::new(transport),
+            ..self
+        }
+    }
+
+    /// Rejects connections of peers for which `authorizer` returns `false`, before any of
+    /// their messages are delivered. All peers are accepted by default.
+    pub fn with_peer_authorizer<F>(self, authorizer: F) -> Self
+    where
+        F: Fn(&Peer) -> bool + Send + Sync + 'static,
+    {
+        Self {
+            authorizer: Some(Arc::new(authorizer)),
+            ..self
+        }
+    }
+
+    /// Maximum number of messages sent to an endpoint in a single batch.
+    pub fn with_batch_size(self, batch_size: usize) -> Self {
+        Self {
+            batch_size: batch_size.max(1),
+            ..self
+        }
+    }
+
+    /// How long to wait for more messages before sending a batch that is not full, batches
+    /// are sent right away by default.
+    pub fn with_batch_linger(self, batch_linger: Duration) -> Self {
+        Self {
+            batch_linger,
+            ..self
+        }
+    }
+
+    /// Offers `compression` for batches sent to remote actor systems, and accepts batches
+    /// compressed with it. Algorithms added first are preferred.
+    pub fn with_compression(mut self, compression: Compression) -> Self {
+        if !self.compressions.contains(&compression) {
+            self.compressions.push(compression);
+        }
+        self
+    }
+
+    pub fn with_connect_timeout(self, connect_timeout: Duration) -> Self {
+        Self {
+            connect_timeout,
+            ..self
+        }
+    }
+
+    /// How often connections send heartbeats, and how long a connection may stay silent
+    /// before the peer is considered dead.
+    pub fn with_heartbeat(self, interval: Duration, timeout: Duration) -> Self {
+        Self {
+            heartbeat_interval: interval,
+            heartbeat_timeout: timeout.max(interval),
+            ..self
+        }
+    }
+
+    /// Delay before reconnecting doubles with every failed attempt, from `initial` up to
+    /// `max`, and is randomly shortened by up to a half.
+    pub fn with_backoff(self, initial: Duration, max: Duration) -> Self {
+        Self {
+            initial_backoff: initial,
+            max_backoff: max.max(initial),
+            ..self
+        }
+    }
+
+    /// Number of failed attempts to reconnect after which the address is considered
+    /// terminated.
+    pub fn with_max_reconnect_attempts(self, max_reconnect_attempts: u32) -> Self {
+        Self {
+            max_reconnect_attempts,
+            ..self
+        }
+    }
+
+    /// Maximum number of messages per address waiting to be sent, for example while
+    /// connecting or reconnecting, messages over the limit go to dead letters right away.
+    pub fn with_max_buffered_messages(self, max_buffered_messages: usize) -> Self {
+        Self {
+            max_buffered_messages,
+            ..self
+        }
+    }
+
+    /// Largest frame in bytes sent to or accepted from remote actor systems. Messages that do
+    /// not fit are returned to their sender as
+    /// [MessageTooLarge](crate::remote::MessageTooLarge), connections of peers sending larger
+    /// frames are closed.
+    pub fn with_max_frame_size(self, max_frame_size: usize) -> Self {
+        Self {
+            max_frame_size,
+            ..self
+        }
+    }
+
+    /// Quarantines a remote address for the `duration` once it makes `max_protocol_errors`,
+    /// such as undecodable frames or messages, within the `duration`. Errors of connecting
+    /// peers count against the address they announce, or the certificate they authenticated
+    /// with, which gets quarantined along with the address.
+    pub fn with_quarantine(self, max_protocol_errors: u32, duration: Duration) -> Self {
+        Self {
+            max_protocol_errors: max_protocol_errors.max(1),
+            quarantine_duration: duration,
+            ..self
+        }
+    }
+
+    /// Lets remote actor systems spawn actors from `props` with
+    /// [Remote::spawn](crate::remote::Remote::spawn) under the `kind` name.
+    pub fn with_kind<K>(mut self, kind: K, props: Props) -> Self
+    where
+        K: Into<String>,
+    {
+        self.kinds.insert(kind.into(), props);
+        self
+    }
+
+    /// How long a silent peer is considered alive, [None] if the transport does not exchange
+    /// heartbeats.
+    pub(crate) fn get_heartbeat_timeout(&self) -> Option<Duration> {
+        self.transport
+            .uses_heartbeats()
+            .then_some(self.heartbeat_timeout)
+    }
+
+    /// Delay before the reconnect `attempt`, starting from 1.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1).min(31);
+        let delay = self
+            .initial_backoff
+            .saturating_mul(1 << exponent)
+            .min(self.max_backoff);
+        delay.mul_f64(rand::thread_rng().gen_range(0.5..=1.0))
+    }
+
+    /// Registers protobuf message `M` to be sent to and received from remote actor systems
+    /// under `type_name`, which must be the same on both sides.
+    pub fn with_message<M>(mut self, type_name: &str) -> Self
+    where
+        M: prost::Message + Message + Default + Send + Sync + 'static,
+    {
+        self.serializers.register_proto::<M>(type_name);
+        self
+    }
+
+    /// Registers serde message `M` serialized as JSON under `type_name`.
+    pub fn with_json_message<M>(mut self, type_name: &str) -> Self
+    where
+        M: Serialize + DeserializeOwned + Message + Send + Sync + 'static,
+    {
+        self.serializers.register_json::<M>(type_name);
+        self
+    }
+
+    /// Registers message `M` serialized by a custom [Serializer] under `type_name`.
+    pub fn with_serializer<M, S>(mut self, type_name: &str, serializer: S) -> Self
+    where
+        M: Message + Send + Sync + 'static,
+        S: Serializer + 'static,
+    {
+        self.serializers.register::<M, S>(type_name, serializer);
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::remote::RemoteConfig;
+    use std::time::Duration;
+
+    #[test]
+    fn should_back_off_exponentially_with_jitter() {
+        let config = RemoteConfig::bind("127.0.0.1", 0)
+            .with_backoff(Duration::from_millis(100), Duration::from_millis(1000));
+
+        for (attempt, max) in [
+            (1, 100),
+            (2, 200),
+            (3, 400),
+            (4, 800),
+            (5, 1000),
+            (40, 1000),
+        ] {
+            let delay = config.backoff(attempt);
+            assert!(delay >= Duration::from_millis(max / 2), "{:?}", delay);
+            assert!(delay <= Duration::from_millis(max), "{:?}", delay);
+        }
+    }
+}
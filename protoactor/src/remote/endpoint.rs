@@ -0,0 +1,702 @@
+use crate::message::{
+    AnyMessage, MessageEnvelope, Pid, SystemMessage, Terminated, TerminatedReason,
+};
+use crate::remote::blocklist::{BlockReason, Blocklist};
+use crate::remote::frame::{read_frame, read_frame_within, write_frame};
+use crate::remote::protos::{
+    self, connect_request, remote_message, CompressedBatch, ConnectRequest, Heartbeat,
+    MessageBatch, RemoteMessage, ServerConnection,
+};
+use crate::remote::transport::BoxConnection;
+use crate::remote::{Compression, MessageBlocked, MessageTooLarge, RemoteConfig};
+use crate::system::{ActorSystem, NO_HOST};
+use log::{debug, info, warn};
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use tokio::io::{ReadHalf, WriteHalf};
+use tokio::sync::{mpsc, Notify};
+use tokio::task::JoinHandle;
+
+/// Message on its way to a process of a remote actor system.
+pub(crate) struct RemoteDelivery {
+    pub(crate) target: Pid,
+    pub(crate) envelope: MessageEnvelope<AnyMessage>,
+}
+
+/// Outgoing connection to a single remote address.
+struct Endpoint {
+    sender: mpsc::Sender<RemoteDelivery>,
+    task: JoinHandle<()>,
+    /// Notified once the address is blocked.
+    blocked: Arc<Notify>,
+}
+
+/// Writing half of a connection, compressing batches with the algorithm negotiated for it.
+struct BatchWriter {
+    writer: WriteHalf<BoxConnection>,
+    compression: Option<Compression>,
+}
+
+impl BatchWriter {
+    async fn write(&mut self, message: &RemoteMessage) -> io::Result<()> {
+        write_frame(&mut self.writer, message).await
+    }
+}
+
+/// Frame carrying the batch, compressed unless there is no compression or it does not make
+/// the frame smaller.
+pub(crate) fn batch_frame(
+    batch: MessageBatch,
+    compression: Option<Compression>,
+) -> io::Result<RemoteMessage> {
+    let data = compression
+        .map(|compression| compression.compress(&prost::Message::encode_to_vec(&batch)))
+        .transpose()?;
+    let uncompressed = RemoteMessage::from(remote_message::MessageType::MessageBatch(batch));
+    let (Some(compression), Some(data)) = (compression, data) else {
+        return Ok(uncompressed);
+    };
+    let compressed = RemoteMessage::from(remote_message::MessageType::CompressedBatch(
+        CompressedBatch {
+            compression: compression.name().to_string(),
+            data,
+        },
+    ));
+    let smaller =
+        prost::Message::encoded_len(&compressed) < prost::Message::encoded_len(&uncompressed);
+    Ok(if smaller { compressed } else { uncompressed })
+}
+
+/// Connects to remote addresses on demand and sends them messages in batches.
+///
+/// Connection is dropped when writing to it fails, the peer closes it or misses heartbeats.
+/// Messages wait in a queue per address, bounded by the maximum of buffered messages, while
+/// connecting or reconnecting, and once reconnecting fails too many times they go to dead
+/// letters and local watchers of processes on the address receive
+/// [Terminated] with [TerminatedReason::AddressTerminated].
+pub(crate) struct EndpointManager {
+    pub(crate) system: Weak<ActorSystem>,
+    pub(crate) config: RemoteConfig,
+    pub(crate) system_id: String,
+    pub(crate) blocklist: Blocklist,
+    endpoints: Mutex<HashMap<String, Endpoint>>,
+    /// Local watchers of remote processes, by remote address and watched process.
+    watches: Mutex<HashMap<String, HashMap<Pid, HashSet<Pid>>>>,
+    stopped: AtomicBool,
+}
+
+impl EndpointManager {
+    pub(crate) fn new(system: &Arc<ActorSystem>, config: RemoteConfig, system_id: String) -> Self {
+        Self {
+            system: Arc::downgrade(system),
+            config,
+            system_id,
+            blocklist: Blocklist::default(),
+            endpoints: Mutex::new(HashMap::new()),
+            watches: Mutex::new(HashMap::new()),
+            stopped: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn send(self: &Arc<Self>, target: Pid, envelope: MessageEnvelope<AnyMessage>) {
+        let delivery = RemoteDelivery { target, envelope };
+        if self.stopped.load(Ordering::Acquire) {
+            return self.dead_letter(delivery);
+        }
+        if let Some(reason) = self.blocklist.get(&delivery.target.address) {
+            return self.blocked(delivery, reason);
+        }
+        let mut endpoints = self.endpoints.lock().unwrap();
+        let endpoint = endpoints
+            .entry(delivery.target.address.clone())
+            .or_insert_with_key(|address| self.start_endpoint(address.clone()));
+        if let Err(
+            mpsc::error::TrySendError::Full(delivery) | mpsc::error::TrySendError::Closed(delivery),
+        ) = endpoint.sender.try_send(delivery)
+        {
+            drop(endpoints);
+            self.dead_letter(delivery);
+        }
+    }
+
+    /// Closes all connections, messages sent afterwards go to dead letters.
+    pub(crate) fn stop(&self) {
+        self.stopped.store(true, Ordering::Release);
+        let endpoints = std::mem::take(&mut *self.endpoints.lock().unwrap());
+        for (address, endpoint) in endpoints {
+            endpoint.task.abort();
+            self.address_terminated(&address);
+        }
+    }
+
+    /// Blocks the address, together with the identity of the peer announcing it, and drops
+    /// the connection to it. Messages waiting to be sent are returned to their senders as
+    /// [MessageBlocked].
+    pub(crate) fn block(
+        &self,
+        address: &str,
+        identity: Option<&str>,
+        reason: BlockReason,
+        duration: Option<Duration>,
+    ) {
+        warn!("Address {} is {}", address, reason);
+        self.blocklist.block(address, identity, reason, duration);
+        if let Some(endpoint) = self.endpoints.lock().unwrap().remove(address) {
+            endpoint.blocked.notify_one();
+        }
+        self.address_terminated(address);
+    }
+
+    /// Quarantines the address once it, or the peer authenticated with the identity, made
+    /// too many protocol errors.
+    pub(crate) fn protocol_errors(&self, address: &str, identity: Option<&str>, count: u32) {
+        if address.is_empty() || count == 0 {
+            return;
+        }
+        let max_errors = self.config.max_protocol_errors;
+        let duration = self.config.quarantine_duration;
+        if let Some(protocol_errors) =
+            self.blocklist
+                .protocol_errors(identity.unwrap_or(address), count, max_errors, duration)
+        {
+            let reason = BlockReason::Quarantined { protocol_errors };
+            self.block(address, identity, reason, Some(duration));
+        }
+    }
+
+    /// Local [Pid] as remote actor systems reach it, it may have been spawned before the local
+    /// actor system got its address.
+    pub(crate) fn with_local_address(&self, pid: &Pid) -> Pid {
+        match (pid.address.as_str(), self.system.upgrade()) {
+            (NO_HOST, Some(system)) => Pid {
+                address: system.address(),
+                ..pid.clone()
+            },
+            _ => pid.clone(),
+        }
+    }
+
+    pub(crate) fn watch(&self, watchee: &Pid, watcher: &Pid) {
+        let watcher = self.with_local_address(watcher);
+        let mut watches = self.watches.lock().unwrap();
+        let watchers = watches.entry(watchee.address.clone()).or_default();
+        watchers.entry(watchee.clone()).or_default().insert(watcher);
+    }
+
+    /// Forgets the watcher, called when it unwatches or receives [Terminated] of the watchee.
+    pub(crate) fn unwatch(&self, watchee: &Pid, watcher: &Pid) {
+        let watcher = &self.with_local_address(watcher);
+        let mut watches = self.watches.lock().unwrap();
+        let Some(watched) = watches.get_mut(&watchee.address) else {
+            return;
+        };
+        if let Some(watchers) = watched.get_mut(watchee) {
+            watchers.remove(watcher);
+            if watchers.is_empty() {
+                watched.remove(watchee);
+            }
+        }
+        if watched.is_empty() {
+            watches.remove(&watchee.address);
+        }
+    }
+
+    /// Notifies local watchers of all processes on the address.
+    fn address_terminated(&self, address: &str) {
+        let Some(watched) = self.watches.lock().unwrap().remove(address) else {
+            return;
+        };
+        let Some(system) = self.system.upgrade() else {
+            return;
+        };
+        for (watchee, watchers) in watched {
+            for watcher in watchers {
+                let terminated = Terminated {
+                    who: Some(watchee.clone()),
+                    why: TerminatedReason::AddressTerminated as i32,
+                };
+                system
+                    .get_process(&watcher)
+                    .send_system_message(&watcher, SystemMessage::Terminated(terminated));
+            }
+        }
+    }
+
+    fn start_endpoint(self: &Arc<Self>, address: String) -> Endpoint {
+        let (sender, receiver) = mpsc::channel(self.config.max_buffered_messages.max(1));
+        let blocked = Arc::new(Notify::new());
+        let task = tokio::spawn(
+            self.clone()
+                .run_endpoint(address, receiver, blocked.clone()),
+        );
+        Endpoint {
+            sender,
+            task,
+            blocked,
+        }
+    }
+
+    /// Keeps the connection to the address, reconnecting with backoff, until reconnecting
+    /// fails too many times or the address is blocked.
+    async fn run_endpoint(
+        self: Arc<Self>,
+        address: String,
+        mut receiver: mpsc::Receiver<RemoteDelivery>,
+        blocked: Arc<Notify>,
+    ) {
+        let mut attempt = 0;
+        let was_blocked = loop {
+            let connected = tokio::select! {
+                connected = self.connect(&address) => connected,
+                _ = blocked.notified() => break true,
+            };
+            match connected {
+                Ok((reader, mut writer)) => {
+                    debug!("Connected to {}", address);
+                    attempt = 0;
+                    let error = self
+                        .run_connection(reader, &mut writer, &mut receiver, &blocked)
+                        .await;
+                    let Some(error) = error else {
+                        break true;
+                    };
+                    warn!("Lost connection to {}: {}", address, error);
+                    self.connection_failed(&address, &error);
+                }
+                Err(error) => {
+                    warn!("Failed to connect to {}: {}", address, error);
+                    self.connection_failed(&address, &error);
+                }
+            }
+            attempt += 1;
+            if attempt > self.config.max_reconnect_attempts {
+                break false;
+            }
+            // Messages sent meanwhile wait in the queue, which holds at most
+            // max_buffered_messages.
+            tokio::select! {
+                _ = tokio::time::sleep(self.config.backoff(attempt)) => {}
+                _ = blocked.notified() => break true,
+            }
+        };
+        receiver.close();
+        let mut buffer = Vec::new();
+        while let Ok(delivery) = receiver.try_recv() {
+            buffer.push(delivery);
+        }
+        if was_blocked {
+            // Block removed the endpoint and notified watchers already.
+            let reason = self.blocklist.get(&address);
+            for delivery in buffer {
+                match reason {
+                    Some(reason) => self.blocked(delivery, reason),
+                    None => self.dead_letter(delivery),
+                }
+            }
+            return;
+        }
+        warn!("Giving up on {} after {} attempts", address, attempt);
+        self.endpoints.lock().unwrap().remove(&address);
+        buffer
+            .into_iter()
+            .for_each(|delivery| self.dead_letter(delivery));
+        self.address_terminated(&address);
+    }
+
+    /// Counts errors caused by the peer violating the protocol, which may quarantine it and
+    /// abort the endpoint.
+    fn connection_failed(&self, address: &str, error: &io::Error) {
+        if error.kind() == io::ErrorKind::InvalidData {
+            self.protocol_errors(address, None, 1);
+        }
+    }
+
+    /// Sends queued messages and heartbeats, returns the error the connection failed with,
+    /// or [None] once the address is blocked.
+    async fn run_connection(
+        &self,
+        reader: ReadHalf<BoxConnection>,
+        writer: &mut BatchWriter,
+        receiver: &mut mpsc::Receiver<RemoteDelivery>,
+        blocked: &Notify,
+    ) -> Option<io::Error> {
+        let heartbeat_timeout = self.config.get_heartbeat_timeout();
+        let peer = watch_peer(reader, self.config.max_frame_size, heartbeat_timeout);
+        tokio::pin!(peer);
+        let mut heartbeat = tokio::time::interval(self.config.heartbeat_interval);
+        loop {
+            let result = tokio::select! {
+                error = &mut peer => return Some(error),
+                _ = blocked.notified() => return None,
+                _ = heartbeat.tick(), if heartbeat_timeout.is_some() => {
+                    let heartbeat = remote_message::MessageType::Heartbeat(Heartbeat {});
+                    write_frame(&mut writer.writer, &RemoteMessage::from(heartbeat)).await
+                }
+                delivery = receiver.recv() => {
+                    let Some(delivery) = delivery else {
+                        return Some(io::Error::other("endpoint stopped"));
+                    };
+                    let deliveries = self.next_batch(delivery, receiver).await;
+                    self.write_batch(writer, deliveries).await
+                }
+            };
+            if let Err(error) = result {
+                return Some(error);
+            }
+        }
+    }
+
+    /// Collects messages following `first` into a batch, waiting up to the batch linger for
+    /// the batch to fill.
+    async fn next_batch(
+        &self,
+        first: RemoteDelivery,
+        receiver: &mut mpsc::Receiver<RemoteDelivery>,
+    ) -> Vec<RemoteDelivery> {
+        let mut deliveries = vec![first];
+        let linger = tokio::time::sleep(self.config.batch_linger);
+        tokio::pin!(linger);
+        while deliveries.len() < self.config.batch_size {
+            match receiver.try_recv() {
+                Ok(delivery) => deliveries.push(delivery),
+                Err(_) if self.config.batch_linger.is_zero() => break,
+                Err(_) => tokio::select! {
+                    _ = &mut linger => break,
+                    Some(delivery) = receiver.recv() => deliveries.push(delivery),
+                },
+            }
+        }
+        deliveries
+    }
+
+    async fn connect(&self, address: &str) -> io::Result<(ReadHalf<BoxConnection>, BatchWriter)> {
+        let timeout = self.config.connect_timeout;
+        let stream = tokio::time::timeout(
+            timeout,
+            self.config.transport.connect(address, &self.config),
+        )
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "connect timed out"))??;
+        let (mut reader, mut writer) = tokio::io::split(stream);
+        write_frame(&mut writer, &self.connect_request()).await?;
+        let max_frame_size = self.config.max_frame_size;
+        let response = tokio::time::timeout(timeout, read_frame(&mut reader, max_frame_size))
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "handshake timed out"))??;
+        match response.and_then(|response| response.message_type) {
+            Some(remote_message::MessageType::ConnectResponse(response)) if !response.blocked => {
+                let compression = match response.compression.as_str() {
+                    "" => None,
+                    name => Some(
+                        Compression::negotiate(&[name], &self.config.compressions).ok_or_else(
+                            || {
+                                let message = format!("unsupported compression {}", name);
+                                io::Error::new(io::ErrorKind::InvalidData, message)
+                            },
+                        )?,
+                    ),
+                };
+                Ok((
+                    reader,
+                    BatchWriter {
+                        writer,
+                        compression,
+                    },
+                ))
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                "connection rejected",
+            )),
+        }
+    }
+
+    /// Announces the local actor system and the compressions it offers.
+    fn connect_request(&self) -> RemoteMessage {
+        let local_address = self.system.upgrade().map(|system| system.address());
+        let connection = ServerConnection {
+            system_id: self.system_id.clone(),
+            address: local_address.unwrap_or_default(),
+            compressions: self
+                .config
+                .compressions
+                .iter()
+                .map(|compression| compression.name().to_string())
+                .collect(),
+        };
+        let request = ConnectRequest {
+            connection_type: Some(connect_request::ConnectionType::ServerConnection(
+                connection,
+            )),
+        };
+        RemoteMessage::from(remote_message::MessageType::ConnectRequest(request))
+    }
+
+    /// Writes the deliveries in batches that fit in a frame, messages that do not fit on their
+    /// own are returned to their senders. Messages of a batch that fails to be written go to
+    /// dead letters, they may have reached the peer.
+    async fn write_batch(
+        &self,
+        writer: &mut BatchWriter,
+        deliveries: Vec<RemoteDelivery>,
+    ) -> io::Result<()> {
+        let mut pending = vec![deliveries];
+        while let Some(deliveries) = pending.pop() {
+            let (batch, mut deliveries) = self.encode_batch(deliveries);
+            if batch.envelopes.is_empty() {
+                continue;
+            }
+            let frame = batch_frame(batch, writer.compression);
+            let size = frame.as_ref().map_or(0, prost::Message::encoded_len);
+            if size > self.config.max_frame_size {
+                if deliveries.len() == 1 {
+                    self.message_too_large(deliveries.remove(0), size);
+                } else {
+                    let second_half = deliveries.split_off(deliveries.len() / 2);
+                    pending.push(second_half);
+                    pending.push(deliveries);
+                }
+                continue;
+            }
+            let written = match frame {
+                Ok(frame) => writer.write(&frame).await,
+                Err(error) => Err(error),
+            };
+            if let Err(error) = written {
+                deliveries
+                    .into_iter()
+                    .chain(pending.into_iter().flatten())
+                    .for_each(|delivery| self.dead_letter(delivery));
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the message to its sender as [MessageTooLarge] and publishes it.
+    fn message_too_large(&self, delivery: RemoteDelivery, size: usize) {
+        let Some(system) = self.system.upgrade() else {
+            return;
+        };
+        let event = MessageTooLarge {
+            target: delivery.target,
+            sender: delivery.envelope.get_sender().clone(),
+            type_name: delivery.envelope.get_message().type_name().to_string(),
+            size,
+            max_size: self.config.max_frame_size,
+        };
+        warn!("Dropping {}", event);
+        if let Some(sender) = &event.sender {
+            let envelope = MessageEnvelope::wrap(AnyMessage::new(event.clone()));
+            system
+                .get_process(sender)
+                .send_user_message(sender, envelope);
+        }
+        system.event_stream().publish(event);
+    }
+
+    /// Encodes deliveries into a batch, returns it with the deliveries it contains. Messages
+    /// that cannot be serialized go to dead letters.
+    fn encode_batch(&self, deliveries: Vec<RemoteDelivery>) -> (MessageBatch, Vec<RemoteDelivery>) {
+        let local_address = self.system.upgrade().map(|system| system.address());
+        let mut type_names = Indexed::default();
+        let mut targets = Indexed::default();
+        let mut senders = Indexed::default();
+        let mut envelopes = Vec::with_capacity(deliveries.len());
+        let mut encoded = Vec::with_capacity(deliveries.len());
+        for delivery in deliveries {
+            let message = delivery.envelope.get_message();
+            let serialized = match self.config.serializers.serialize(message) {
+                Ok(serialized) => serialized,
+                Err(error) => {
+                    warn!("Cannot send to {}: {}", delivery.target, error);
+                    self.dead_letter(delivery);
+                    continue;
+                }
+            };
+            let (target, target_request_id) = split_request_id(&delivery.target);
+            let (sender, sender_request_id) = match delivery.envelope.get_sender() {
+                Some(sender) => {
+                    let (mut sender, request_id) = split_request_id(sender);
+                    if let (NO_HOST, Some(address)) = (sender.address.as_str(), &local_address) {
+                        sender.address = address.clone();
+                    }
+                    (senders.index_of(sender) as i32 + 1, request_id)
+                }
+                None => (0, 0),
+            };
+            let header = delivery.envelope.get_header();
+            envelopes.push(protos::MessageEnvelope {
+                type_id: type_names.index_of(serialized.type_name) as i32,
+                message_data: serialized.data,
+                target: targets.index_of(target) as i32,
+                sender,
+                serializer_id: serialized.serializer_id,
+                message_header: (!header.is_empty()).then(|| protos::MessageHeader {
+                    header_data: header.clone(),
+                }),
+                target_request_id,
+                sender_request_id,
+            });
+            encoded.push(delivery);
+        }
+        let batch = MessageBatch {
+            type_names: type_names.items,
+            targets: targets.items,
+            envelopes,
+            senders: senders.items,
+        };
+        (batch, encoded)
+    }
+
+    /// Returns the message to its sender as [MessageBlocked] and publishes it, messages
+    /// without a sender go to dead letters.
+    fn blocked(&self, delivery: RemoteDelivery, reason: BlockReason) {
+        let Some(system) = self.system.upgrade() else {
+            return;
+        };
+        let event = MessageBlocked {
+            target: delivery.target.clone(),
+            sender: delivery.envelope.get_sender().clone(),
+            type_name: delivery.envelope.get_message().type_name().to_string(),
+            reason,
+        };
+        info!("Dropping {}", event);
+        match &event.sender {
+            Some(sender) => {
+                let envelope = MessageEnvelope::wrap(AnyMessage::new(event.clone()));
+                system
+                    .get_process(sender)
+                    .send_user_message(sender, envelope);
+            }
+            None => self.dead_letter(delivery),
+        }
+        system.event_stream().publish(event);
+    }
+
+    fn dead_letter(&self, delivery: RemoteDelivery) {
+        if let Some(system) = self.system.upgrade() {
+            system
+                .dead_letter()
+                .send_user_message(&delivery.target, delivery.envelope);
+        }
+    }
+}
+
+/// Completes with an error once the peer closes the connection or stays silent for longer
+/// than `timeout`. Peer sends nothing but heartbeats on connections it accepted.
+async fn watch_peer(
+    mut reader: ReadHalf<BoxConnection>,
+    max_frame_size: usize,
+    timeout: Option<Duration>,
+) -> io::Error {
+    loop {
+        match read_frame_within(&mut reader, max_frame_size, timeout).await {
+            Ok(Some(_)) => {}
+            Ok(None) => return io::Error::new(io::ErrorKind::ConnectionAborted, "closed by peer"),
+            Err(error) => return error,
+        }
+    }
+}
+
+impl From<remote_message::MessageType> for RemoteMessage {
+    fn from(message_type: remote_message::MessageType) -> Self {
+        Self {
+            message_type: Some(message_type),
+        }
+    }
+}
+
+/// Request id travels in the envelope, so that [Pid]s in a batch are shared by requests.
+fn split_request_id(pid: &Pid) -> (Pid, u32) {
+    (
+        Pid::new(pid.address.as_str(), pid.id.as_str()),
+        pid.request_id,
+    )
+}
+
+/// Values deduplicated into a list referenced by index.
+struct Indexed<T> {
+    items: Vec<T>,
+    indexes: HashMap<T, usize>,
+}
+
+impl<T> Default for Indexed<T> {
+    fn default() -> Self {
+        Self {
+            items: Vec::new(),
+            indexes: HashMap::new(),
+        }
+    }
+}
+
+impl<T> Indexed<T>
+where
+    T: Clone + Eq + Hash,
+{
+    fn index_of(&mut self, item: T) -> usize {
+        if let Some(index) = self.indexes.get(&item) {
+            return *index;
+        }
+        self.items.push(item.clone());
+        self.indexes.insert(item, self.items.len() - 1);
+        self.items.len() - 1
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::{AnyMessage, MessageEnvelope, Pid, Touch, Watch};
+    use crate::remote::endpoint::{EndpointManager, RemoteDelivery};
+    use crate::remote::protos::{remote_message, RemoteMessage};
+    use crate::remote::tests::{config, hex, new_system, BATCH_FRAME, CONNECT_REQUEST_FRAME};
+    use crate::system::NO_HOST;
+    use prost::Message;
+
+    #[tokio::test]
+    async fn should_encode_connect_request() {
+        let system = new_system();
+        system.set_address("127.0.0.1:9000".to_string());
+        let endpoints = EndpointManager::new(&system, config(0), "go-system".into());
+
+        let request = endpoints.connect_request();
+
+        assert_eq!(hex(CONNECT_REQUEST_FRAME), request.encode_to_vec());
+    }
+
+    #[tokio::test]
+    async fn should_encode_batches() {
+        let system = new_system();
+        system.set_address("127.0.0.1:9000".to_string());
+        let endpoints = EndpointManager::new(&system, config(0), "rust-system".into());
+        let echo = Pid::new("127.0.0.1:9100", "echo");
+        let watch = Watch {
+            watcher: Some(Pid::new("127.0.0.1:9000", "watcher")),
+        };
+        let sender = Pid {
+            request_id: 7,
+            ..Pid::new(NO_HOST, "watcher")
+        };
+        let header = [("tenant".to_string(), "go".to_string())].into();
+        let touch = MessageEnvelope::new(AnyMessage::new(Touch {}), Some(sender), Some(header));
+        let deliveries = vec![
+            RemoteDelivery {
+                target: echo.clone(),
+                envelope: MessageEnvelope::new(AnyMessage::new(watch), None, None),
+            },
+            RemoteDelivery {
+                target: echo,
+                envelope: touch,
+            },
+        ];
+
+        let (batch, _) = endpoints.encode_batch(deliveries);
+
+        let message = RemoteMessage::from(remote_message::MessageType::MessageBatch(batch));
+        assert_eq!(hex(BATCH_FRAME), message.encode_to_vec());
+    }
+}
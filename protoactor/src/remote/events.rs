@@ -0,0 +1,66 @@
+use crate::message::{Message, Pid};
+use crate::remote::BlockReason;
+use std::fmt::{Display, Formatter};
+
+/// Message that does not fit in a frame of the remote transport, see
+/// [RemoteConfig::with_max_frame_size](crate::remote::RemoteConfig::with_max_frame_size).
+///
+/// It is published on the [EventStream](crate::system::EventStream) and returned to the sender
+/// of the message, requests fail with [RequestError::MessageTooLarge].
+///
+/// [RequestError::MessageTooLarge]: crate::process::RequestError::MessageTooLarge
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageTooLarge {
+    pub target: Pid,
+    pub sender: Option<Pid>,
+    /// Type name of the message, see [AnyMessage::type_name](crate::message::AnyMessage::type_name).
+    pub type_name: String,
+    /// Size in bytes of the smallest frame carrying the message.
+    pub size: usize,
+    pub max_size: usize,
+}
+
+impl Message for MessageTooLarge {
+    type Result = ();
+}
+
+impl Display for MessageTooLarge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "message {} to {} of {} bytes exceeds the maximum frame size of {}",
+            self.type_name, self.target, self.size, self.max_size
+        )
+    }
+}
+
+/// Message to an address that is blocked or quarantined, see
+/// [Remote::block](crate::remote::Remote::block).
+///
+/// It is published on the [EventStream](crate::system::EventStream) and returned to the sender
+/// of the message, requests fail with [RequestError::Blocked]. Messages without a sender go to
+/// dead letters.
+///
+/// [RequestError::Blocked]: crate::process::RequestError::Blocked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageBlocked {
+    pub target: Pid,
+    pub sender: Option<Pid>,
+    /// Type name of the message, see [AnyMessage::type_name](crate::message::AnyMessage::type_name).
+    pub type_name: String,
+    pub reason: BlockReason,
+}
+
+impl Message for MessageBlocked {
+    type Result = ();
+}
+
+impl Display for MessageBlocked {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "message {} to {}, its address is {}",
+            self.type_name, self.target, self.reason
+        )
+    }
+}
@@ -0,0 +1,128 @@
+use crate::remote::protos::RemoteMessage;
+use prost::Message;
+use std::io;
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+/// Writes the message prefixed with its varint encoded length.
+pub(crate) async fn write_frame<W>(writer: &mut W, message: &RemoteMessage) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    writer
+        .write_all(&message.encode_length_delimited_to_vec())
+        .await?;
+    writer.flush().await
+}
+
+/// Reads the next length delimited message, or [None] if the peer closed the connection
+/// between frames. Frames over `max_size` bytes fail with [io::ErrorKind::InvalidData].
+pub(crate) async fn read_frame<R>(
+    reader: &mut R,
+    max_size: usize,
+) -> io::Result<Option<RemoteMessage>>
+where
+    R: AsyncRead + Unpin,
+{
+    let Some(frame) = read_frame_bytes(reader, max_size).await? else {
+        return Ok(None);
+    };
+    RemoteMessage::decode(frame.as_slice())
+        .map(Some)
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+/// Reads the next message like [read_frame], failing with [io::ErrorKind::TimedOut] if it does
+/// not arrive within the `timeout`.
+pub(crate) async fn read_frame_within<R>(
+    reader: &mut R,
+    max_size: usize,
+    timeout: Option<Duration>,
+) -> io::Result<Option<RemoteMessage>>
+where
+    R: AsyncRead + Unpin,
+{
+    let Some(timeout) = timeout else {
+        return read_frame(reader, max_size).await;
+    };
+    tokio::time::timeout(timeout, read_frame(reader, max_size))
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "peer missed heartbeats"))?
+}
+
+/// Reads body of the next frame without decoding it.
+pub(crate) async fn read_frame_bytes<R>(
+    reader: &mut R,
+    max_size: usize,
+) -> io::Result<Option<Vec<u8>>>
+where
+    R: AsyncRead + Unpin,
+{
+    let mut length = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = match reader.read_u8().await {
+            Ok(byte) => byte,
+            Err(error) if error.kind() == io::ErrorKind::UnexpectedEof && shift == 0 => {
+                return Ok(None)
+            }
+            Err(error) => return Err(error),
+        };
+        length |= ((byte & 0x7f) as u64) << shift;
+        if byte & 0x80 == 0 {
+            check_frame_size(length, max_size)?;
+            let mut buffer = vec![0; length as usize];
+            reader.read_exact(&mut buffer).await?;
+            return Ok(Some(buffer));
+        }
+    }
+    Err(io::Error::new(
+        io::ErrorKind::InvalidData,
+        "frame length is not a valid varint",
+    ))
+}
+
+/// Fails with [io::ErrorKind::InvalidData] if the frame is larger than `max_size` bytes.
+pub(crate) fn check_frame_size(size: u64, max_size: usize) -> io::Result<()> {
+    if size > max_size as u64 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "frame of {} bytes exceeds the maximum of {}",
+                size, max_size
+            ),
+        ));
+    }
+    Ok(())
+}
+
+/// Writes frame body read by [read_frame_bytes].
+pub(crate) async fn write_frame_bytes<W>(writer: &mut W, frame: &[u8]) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    let mut buffer = Vec::with_capacity(frame.len() + 10);
+    prost::encoding::encode_varint(frame.len() as u64, &mut buffer);
+    buffer.extend_from_slice(frame);
+    writer.write_all(&buffer).await?;
+    writer.flush().await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::remote::frame::{read_frame_bytes, write_frame_bytes};
+    use std::io;
+
+    #[tokio::test]
+    async fn should_refuse_frames_over_max_size() {
+        let mut buffer = Vec::new();
+        write_frame_bytes(&mut buffer, &[1; 10]).await.unwrap();
+
+        let frame = read_frame_bytes(&mut buffer.as_slice(), 10).await.unwrap();
+        let error = read_frame_bytes(&mut buffer.as_slice(), 9)
+            .await
+            .unwrap_err();
+
+        assert_eq!(Some(vec![1; 10]), frame);
+        assert_eq!(io::ErrorKind::InvalidData, error.kind());
+    }
+}
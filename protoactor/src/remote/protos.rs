@@ -0,0 +1,11 @@
+use crate::message::Message;
+
+include!(concat!(env!("OUT_DIR"), "/remote/remote.rs"));
+
+impl Message for ActorPidRequest {
+    type Result = ActorPidResponse;
+}
+
+impl Message for ActorPidResponse {
+    type Result = ();
+}
@@ -0,0 +1,65 @@
+use crate::message::{
+    AnyMessage, Message, MessageEnvelope, Pid, Stop, SystemMessage, Unwatch, Watch,
+};
+use crate::process::Process;
+use crate::remote::endpoint::EndpointManager;
+use crate::system::ActorSystem;
+use log::debug;
+use std::sync::{Arc, Weak};
+
+/// Stands for every process of remote actor systems, forwards messages to their endpoints.
+pub(crate) struct RemoteProcess {
+    system: Weak<ActorSystem>,
+    endpoints: Arc<EndpointManager>,
+}
+
+impl RemoteProcess {
+    pub(crate) fn new(system: &Arc<ActorSystem>, endpoints: Arc<EndpointManager>) -> Self {
+        Self {
+            system: Arc::downgrade(system),
+            endpoints,
+        }
+    }
+
+    fn send<M>(&self, pid: &Pid, message: M)
+    where
+        M: Message + Send + Sync + 'static,
+    {
+        let envelope = MessageEnvelope::wrap(AnyMessage::new(message));
+        self.endpoints.send(pid.clone(), envelope);
+    }
+}
+
+impl Process for RemoteProcess {
+    fn system(&self) -> Arc<ActorSystem> {
+        self.system
+            .upgrade()
+            .expect("remote process outlived its actor system")
+    }
+
+    fn send_user_message(&self, pid: &Pid, envelope: MessageEnvelope<AnyMessage>) {
+        self.endpoints.send(pid.clone(), envelope);
+    }
+
+    fn send_system_message(&self, pid: &Pid, msg: SystemMessage) {
+        match msg {
+            SystemMessage::Stop => self.send(pid, Stop {}),
+            SystemMessage::Watch(Watch {
+                watcher: Some(watcher),
+            }) => {
+                self.endpoints.watch(pid, &watcher);
+                let watcher = Some(self.endpoints.with_local_address(&watcher));
+                self.send(pid, Watch { watcher });
+            }
+            SystemMessage::Unwatch(Unwatch {
+                watcher: Some(watcher),
+            }) => {
+                self.endpoints.unwatch(pid, &watcher);
+                let watcher = Some(self.endpoints.with_local_address(&watcher));
+                self.send(pid, Unwatch { watcher });
+            }
+            SystemMessage::Terminated(terminated) => self.send(pid, terminated),
+            _ => debug!("System message to remote {} is not supported", pid),
+        }
+    }
+}
@@ -0,0 +1,326 @@
+use crate::message::{
+    AnyMessage, DeadLetterResponse, Message, PoisonPill, Stop, Terminated, Touch, Touched, Unwatch,
+    Watch,
+};
+use crate::remote::protos::{ActorPidRequest, ActorPidResponse};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+/// Id of [ProtoSerializer] on the wire.
+pub const PROTO_SERIALIZER_ID: i32 = 0;
+/// Id of [JsonSerializer] on the wire.
+pub const JSON_SERIALIZER_ID: i32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SerializationError {
+    /// Message type was not registered. Holds the Rust type name.
+    UnknownType(&'static str),
+    /// Message arrived with a type name that was not registered.
+    UnknownTypeName(String),
+    /// Message arrived serialized differently than its type was registered with.
+    SerializerMismatch {
+        type_name: String,
+        serializer_id: i32,
+    },
+    /// Message could not be encoded.
+    Encode(String),
+    /// Message could not be decoded.
+    Decode(String),
+}
+
+impl Display for SerializationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SerializationError::UnknownType(type_name) => {
+                write!(f, "message type {} is not registered", type_name)
+            }
+            SerializationError::UnknownTypeName(type_name) => {
+                write!(f, "remote message type {} is not registered", type_name)
+            }
+            SerializationError::SerializerMismatch {
+                type_name,
+                serializer_id,
+            } => write!(
+                f,
+                "remote message type {} is not registered with serializer {}",
+                type_name, serializer_id
+            ),
+            SerializationError::Encode(error) => write!(f, "failed to encode message: {}", error),
+            SerializationError::Decode(error) => write!(f, "failed to decode message: {}", error),
+        }
+    }
+}
+
+impl Error for SerializationError {}
+
+/// Converts messages of a single type to bytes and back.
+pub trait Serializer: Send + Sync {
+    /// Sent with every message, must identify the same format on all actor systems.
+    fn serializer_id(&self) -> i32;
+
+    /// Called only with messages of the type the serializer was registered for.
+    fn serialize(&self, message: &AnyMessage) -> Result<Vec<u8>, SerializationError>;
+
+    fn deserialize(&self, bytes: &[u8]) -> Result<AnyMessage, SerializationError>;
+}
+
+/// Serializes prost messages, the default for remote messages.
+pub struct ProtoSerializer<M>(PhantomData<fn() -> M>);
+
+impl<M> Default for ProtoSerializer<M> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<M> Serializer for ProtoSerializer<M>
+where
+    M: prost::Message + Message + Default + Send + Sync + 'static,
+{
+    fn serializer_id(&self) -> i32 {
+        PROTO_SERIALIZER_ID
+    }
+
+    fn serialize(&self, message: &AnyMessage) -> Result<Vec<u8>, SerializationError> {
+        message
+            .downcast_ref::<M>()
+            .map(prost::Message::encode_to_vec)
+            .ok_or(SerializationError::UnknownType(message.type_name()))
+    }
+
+    fn deserialize(&self, bytes: &[u8]) -> Result<AnyMessage, SerializationError> {
+        M::decode(bytes)
+            .map(AnyMessage::new)
+            .map_err(|error| SerializationError::Decode(error.to_string()))
+    }
+}
+
+/// Serializes serde messages as JSON.
+pub struct JsonSerializer<M>(PhantomData<fn() -> M>);
+
+impl<M> Default for JsonSerializer<M> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<M> Serializer for JsonSerializer<M>
+where
+    M: Serialize + DeserializeOwned + Message + Send + Sync + 'static,
+{
+    fn serializer_id(&self) -> i32 {
+        JSON_SERIALIZER_ID
+    }
+
+    fn serialize(&self, message: &AnyMessage) -> Result<Vec<u8>, SerializationError> {
+        let message = message
+            .downcast_ref::<M>()
+            .ok_or(SerializationError::UnknownType(message.type_name()))?;
+        serde_json::to_vec(message).map_err(|error| SerializationError::Encode(error.to_string()))
+    }
+
+    fn deserialize(&self, bytes: &[u8]) -> Result<AnyMessage, SerializationError> {
+        serde_json::from_slice::<M>(bytes)
+            .map(AnyMessage::new)
+            .map_err(|error| SerializationError::Decode(error.to_string()))
+    }
+}
+
+/// Message serialized for the wire.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerializedMessage {
+    pub type_name: String,
+    pub serializer_id: i32,
+    pub data: Vec<u8>,
+}
+
+/// [Serializer]s of message types that can be sent to remote actor systems, keyed by the type
+/// name used on the wire.
+#[derive(Clone)]
+pub struct SerializerRegistry {
+    type_names: HashMap<TypeId, String>,
+    serializers: HashMap<String, Arc<dyn Serializer>>,
+}
+
+impl Default for SerializerRegistry {
+    /// Registry with the system messages of the `actor` and `remote` proto packages.
+    fn default() -> Self {
+        let mut registry = Self {
+            type_names: HashMap::new(),
+            serializers: HashMap::new(),
+        };
+        registry.register_proto::<PoisonPill>("actor.PoisonPill");
+        registry.register_proto::<Stop>("actor.Stop");
+        registry.register_proto::<Watch>("actor.Watch");
+        registry.register_proto::<Unwatch>("actor.Unwatch");
+        registry.register_proto::<Terminated>("actor.Terminated");
+        registry.register_proto::<DeadLetterResponse>("actor.DeadLetterResponse");
+        registry.register_proto::<Touch>("actor.Touch");
+        registry.register_proto::<Touched>("actor.Touched");
+        registry.register_proto::<ActorPidRequest>("remote.ActorPidRequest");
+        registry.register_proto::<ActorPidResponse>("remote.ActorPidResponse");
+        registry
+    }
+}
+
+impl SerializerRegistry {
+    /// Registers messages of type `M` under `type_name`, replacing previous registration of
+    /// the type or the name.
+    pub fn register<M, S>(&mut self, type_name: &str, serializer: S)
+    where
+        M: Message + Send + Sync + 'static,
+        S: Serializer + 'static,
+    {
+        let type_id = TypeId::of::<M>();
+        if let Some(previous) = self.type_names.remove(&type_id) {
+            self.serializers.remove(&previous);
+        }
+        self.type_names.retain(|_, name| name != type_name);
+        self.type_names.insert(type_id, type_name.to_string());
+        self.serializers
+            .insert(type_name.to_string(), Arc::new(serializer));
+    }
+
+    pub fn register_proto<M>(&mut self, type_name: &str)
+    where
+        M: prost::Message + Message + Default + Send + Sync + 'static,
+    {
+        self.register::<M, _>(type_name, ProtoSerializer::<M>::default());
+    }
+
+    pub fn register_json<M>(&mut self, type_name: &str)
+    where
+        M: Serialize + DeserializeOwned + Message + Send + Sync + 'static,
+    {
+        self.register::<M, _>(type_name, JsonSerializer::<M>::default());
+    }
+
+    pub fn serialize(&self, message: &AnyMessage) -> Result<SerializedMessage, SerializationError> {
+        let type_name = self
+            .type_names
+            .get(&message.message_type_id())
+            .ok_or(SerializationError::UnknownType(message.type_name()))?;
+        let serializer = &self.serializers[type_name];
+        Ok(SerializedMessage {
+            type_name: type_name.clone(),
+            serializer_id: serializer.serializer_id(),
+            data: serializer.serialize(message)?,
+        })
+    }
+
+    pub fn deserialize(
+        &self,
+        type_name: &str,
+        serializer_id: i32,
+        bytes: &[u8],
+    ) -> Result<AnyMessage, SerializationError> {
+        let serializer = self
+            .serializers
+            .get(type_name)
+            .ok_or_else(|| SerializationError::UnknownTypeName(type_name.to_string()))?;
+        if serializer.serializer_id() != serializer_id {
+            return Err(SerializationError::SerializerMismatch {
+                type_name: type_name.to_string(),
+                serializer_id,
+            });
+        }
+        serializer.deserialize(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::{AnyMessage, Message, Pid, Terminated, TerminatedReason};
+    use crate::remote::{SerializationError, SerializerRegistry, JSON_SERIALIZER_ID};
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Greeting {
+        name: String,
+    }
+
+    impl Message for Greeting {
+        type Result = ();
+    }
+
+    #[test]
+    fn should_round_trip_system_and_json_messages() {
+        let mut registry = SerializerRegistry::default();
+        registry.register_json::<Greeting>("test.Greeting");
+        let terminated = Terminated {
+            who: Some(Pid::new("host:1", "a")),
+            why: TerminatedReason::Stopped as i32,
+        };
+
+        let serialized = registry.serialize(&AnyMessage::new(terminated.clone()));
+        let serialized = serialized.unwrap();
+        let message = registry.deserialize(&serialized.type_name, 0, &serialized.data);
+        assert_eq!(Some(&terminated), message.unwrap().downcast_ref());
+
+        let greeting = AnyMessage::new(Greeting { name: "a".into() });
+        let serialized = registry.serialize(&greeting).unwrap();
+        assert_eq!(JSON_SERIALIZER_ID, serialized.serializer_id);
+        assert_eq!(br#"{"name":"a"}"#, serialized.data.as_slice());
+        let message = registry.deserialize("test.Greeting", JSON_SERIALIZER_ID, &serialized.data);
+        assert_eq!(
+            greeting.downcast_ref::<Greeting>(),
+            message.unwrap().downcast_ref()
+        );
+    }
+
+    #[test]
+    fn should_reject_unregistered_messages() {
+        let registry = SerializerRegistry::default();
+
+        assert!(matches!(
+            registry.serialize(&AnyMessage::new(Greeting { name: "a".into() })),
+            Err(SerializationError::UnknownType(_))
+        ));
+        assert!(matches!(
+            registry.deserialize("test.Greeting", JSON_SERIALIZER_ID, b"{}"),
+            Err(SerializationError::UnknownTypeName(_))
+        ));
+        assert!(matches!(
+            registry.deserialize("actor.Stop", JSON_SERIALIZER_ID, b"{}"),
+            Err(SerializationError::SerializerMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn should_replace_registrations_of_the_type_or_the_name() {
+        let mut registry = SerializerRegistry::default();
+        registry.register_json::<Greeting>("test.Greeting");
+        registry.register_json::<Greeting>("test.Hello");
+
+        let greeting = AnyMessage::new(Greeting { name: "a".into() });
+        assert_eq!(
+            "test.Hello",
+            registry.serialize(&greeting).unwrap().type_name
+        );
+        assert!(matches!(
+            registry.deserialize("test.Greeting", JSON_SERIALIZER_ID, b"{}"),
+            Err(SerializationError::UnknownTypeName(_))
+        ));
+
+        registry.register_proto::<Terminated>("test.Hello");
+        assert!(matches!(
+            registry.serialize(&greeting),
+            Err(SerializationError::UnknownType(_))
+        ));
+        let terminated = AnyMessage::new(Terminated::default());
+        assert_eq!(
+            "test.Hello",
+            registry.serialize(&terminated).unwrap().type_name
+        );
+        assert!(matches!(
+            registry.deserialize("actor.Terminated", 0, &[]),
+            Err(SerializationError::UnknownTypeName(_))
+        ));
+    }
+}
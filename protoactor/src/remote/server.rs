@@ -0,0 +1,357 @@
+use crate::message::{MessageEnvelope, Pid, Stop, SystemMessage, Terminated, Unwatch, Watch};
+use crate::remote::blocklist::{identity_of, BlockReason};
+use crate::remote::endpoint::EndpointManager;
+use crate::remote::frame::{read_frame, read_frame_within, write_frame};
+use crate::remote::protos::connect_request::ConnectionType;
+use crate::remote::protos::{
+    remote_message, CompressedBatch, ConnectResponse, MessageBatch, RemoteMessage,
+};
+use crate::remote::transport::{BoxConnection, Listener, Peer};
+use crate::remote::Compression;
+use log::{debug, info, warn};
+use prost::Message;
+use std::io;
+use std::sync::Arc;
+use tokio::io::{ReadHalf, WriteHalf};
+use tokio::task::JoinSet;
+
+/// Accepts connections of remote actor systems until the task is aborted, which also closes
+/// all accepted connections.
+pub(crate) async fn serve(mut listener: Box<dyn Listener>, endpoints: Arc<EndpointManager>) {
+    let mut connections = JoinSet::new();
+    loop {
+        tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok((stream, peer)) => {
+                    let endpoints = endpoints.clone();
+                    connections.spawn(async move {
+                        if let Err(error) = handle_connection(stream, &peer, &endpoints).await {
+                            warn!("Connection from {} failed: {}", peer, error);
+                        }
+                    });
+                }
+                Err(error) => warn!("Failed to accept connection: {}", error),
+            },
+            Some(_) = connections.join_next(), if !connections.is_empty() => {}
+        }
+    }
+}
+
+/// Refuses peers that do not announce their address, blocks and protocol errors of the others
+/// are kept by that address and by the identity of peers authenticated with certificates.
+async fn handle_connection(
+    stream: BoxConnection,
+    remote_address: &str,
+    endpoints: &EndpointManager,
+) -> io::Result<()> {
+    let certificates = stream.peer_certificates();
+    let (mut reader, mut writer) = tokio::io::split(stream);
+    let max_frame_size = endpoints.config.max_frame_size;
+    let announced = match read_frame(&mut reader, max_frame_size)
+        .await?
+        .and_then(|message| message.message_type)
+    {
+        Some(remote_message::MessageType::ConnectRequest(request)) => {
+            debug!("Accepted connection {:?}", request.connection_type);
+            match request.connection_type {
+                Some(ConnectionType::ServerConnection(connection))
+                    if !connection.address.is_empty() =>
+                {
+                    Some((connection.address, connection.compressions))
+                }
+                _ => None,
+            }
+        }
+        _ => {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "expected connect request",
+            ))
+        }
+    };
+    let Some((address, compressions)) = announced else {
+        let response = ConnectResponse {
+            blocked: true,
+            member_id: endpoints.system_id.clone(),
+            ..Default::default()
+        };
+        let response = remote_message::MessageType::ConnectResponse(response);
+        write_frame(&mut writer, &RemoteMessage::from(response)).await?;
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "peer did not announce its address",
+        ));
+    };
+    let peer = Peer {
+        address,
+        remote_address: remote_address.to_string(),
+        certificates,
+    };
+    let identity = identity_of(&peer.certificates);
+    let identity = identity.as_deref();
+    let blocked = endpoints.blocklist.get_peer(&peer.address, identity);
+    let authorized = match &endpoints.config.authorizer {
+        Some(authorizer) => blocked.is_none() && authorizer(&peer),
+        None => blocked.is_none(),
+    };
+    let compression = Compression::negotiate(&compressions, &endpoints.config.compressions);
+    let response = ConnectResponse {
+        blocked: !authorized,
+        member_id: endpoints.system_id.clone(),
+        compression: compression
+            .map(|compression| compression.name().to_string())
+            .unwrap_or_default(),
+    };
+    let response = remote_message::MessageType::ConnectResponse(response);
+    write_frame(&mut writer, &RemoteMessage::from(response)).await?;
+    if let Some(reason) = blocked {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("peer {} is {}", peer.address, reason),
+        ));
+    }
+    if !authorized {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!("peer {} is not authorized", peer.address),
+        ));
+    }
+    if let Some(identity) = identity {
+        endpoints.blocklist.connect(&peer.address, identity);
+    }
+    let result = receive(
+        &mut reader,
+        &mut writer,
+        &peer.address,
+        identity,
+        compression,
+        endpoints,
+    )
+    .await;
+    if let Err(error) = &result {
+        if error.kind() == io::ErrorKind::InvalidData {
+            endpoints.protocol_errors(&peer.address, identity, 1);
+        }
+    }
+    if let Some(identity) = identity {
+        endpoints.blocklist.disconnect(&peer.address, identity);
+    }
+    result
+}
+
+/// Delivers batches of the peer, announcing the address and authenticated with the identity,
+/// until it disconnects or gets blocked. Messages of the batch read once it is blocked go to
+/// dead letters.
+async fn receive(
+    reader: &mut ReadHalf<BoxConnection>,
+    writer: &mut WriteHalf<BoxConnection>,
+    address: &str,
+    identity: Option<&str>,
+    compression: Option<Compression>,
+    endpoints: &EndpointManager,
+) -> io::Result<()> {
+    let timeout = endpoints.config.get_heartbeat_timeout();
+    loop {
+        let message = read_frame_within(reader, endpoints.config.max_frame_size, timeout).await?;
+        let blocked = endpoints.blocklist.get_peer(address, identity);
+        match message.and_then(|message| message.message_type) {
+            Some(remote_message::MessageType::MessageBatch(batch)) => {
+                let dropped = deliver(endpoints, batch, blocked);
+                endpoints.protocol_errors(address, identity, dropped);
+            }
+            Some(remote_message::MessageType::CompressedBatch(batch)) => {
+                let max_size = endpoints.config.max_frame_size;
+                let batch = decompress(compression, batch, max_size)?;
+                let dropped = deliver(endpoints, batch, blocked);
+                endpoints.protocol_errors(address, identity, dropped);
+            }
+            Some(remote_message::MessageType::Heartbeat(heartbeat)) => {
+                let heartbeat = remote_message::MessageType::Heartbeat(heartbeat);
+                write_frame(writer, &RemoteMessage::from(heartbeat)).await?;
+            }
+            Some(remote_message::MessageType::DisconnectRequest(_)) | None => return Ok(()),
+            Some(message) => {
+                warn!("Unexpected message from {}: {:?}", address, message);
+                endpoints.protocol_errors(address, identity, 1);
+            }
+        }
+        if let Some(reason) = blocked {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!("peer {} is {}", address, reason),
+            ));
+        }
+    }
+}
+
+/// Decodes batch compressed with the algorithm negotiated for the connection.
+fn decompress(
+    compression: Option<Compression>,
+    batch: CompressedBatch,
+    max_size: usize,
+) -> io::Result<MessageBatch> {
+    let compression = compression
+        .filter(|compression| compression.name() == batch.compression)
+        .ok_or_else(|| {
+            let message = format!("unexpected compression {}", batch.compression);
+            io::Error::new(io::ErrorKind::InvalidData, message)
+        })?;
+    let data = compression.decompress(&batch.data, max_size)?;
+    MessageBatch::decode(data.as_slice())
+        .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error))
+}
+
+/// Delivers messages of the batch to local processes, system messages of the `actor` proto
+/// package as [SystemMessage]s, or to dead letters if the peer is `blocked`. Returns the
+/// number of messages dropped because they could not be decoded.
+fn deliver(endpoints: &EndpointManager, batch: MessageBatch, blocked: Option<BlockReason>) -> u32 {
+    let Some(system) = endpoints.system.upgrade() else {
+        return 0;
+    };
+    let mut dropped = 0;
+    let with_request_id = |pids: &[Pid], index: usize, request_id: u32| {
+        pids.get(index).map(|pid| Pid {
+            request_id,
+            ..pid.clone()
+        })
+    };
+    for envelope in batch.envelopes {
+        let target = with_request_id(
+            &batch.targets,
+            envelope.target as usize,
+            envelope.target_request_id,
+        );
+        let sender = match envelope.sender {
+            0 => None,
+            sender => with_request_id(
+                &batch.senders,
+                sender as usize - 1,
+                envelope.sender_request_id,
+            ),
+        };
+        let (Some(target), Some(type_name)) =
+            (target, batch.type_names.get(envelope.type_id as usize))
+        else {
+            warn!("Dropping remote message with invalid target or type");
+            dropped += 1;
+            continue;
+        };
+        let message = match endpoints.config.serializers.deserialize(
+            type_name,
+            envelope.serializer_id,
+            &envelope.message_data,
+        ) {
+            Ok(message) => message,
+            Err(error) => {
+                warn!("Dropping remote message to {}: {}", target, error);
+                dropped += 1;
+                continue;
+            }
+        };
+        let header = envelope.message_header.map(|header| header.header_data);
+        if let Some(reason) = blocked {
+            info!(
+                "Dropping {} to {}, its sender is {}",
+                type_name, target, reason
+            );
+            let envelope = MessageEnvelope::new(message, sender, header);
+            system.dead_letter().send_user_message(&target, envelope);
+            continue;
+        }
+        let process = system.get_process(&target);
+        let system_message = if message.is::<Stop>() {
+            Some(SystemMessage::Stop)
+        } else if let Some(watch) = message.downcast_ref::<Watch>() {
+            Some(SystemMessage::Watch(watch.clone()))
+        } else if let Some(unwatch) = message.downcast_ref::<Unwatch>() {
+            Some(SystemMessage::Unwatch(unwatch.clone()))
+        } else if let Some(terminated) = message.downcast_ref::<Terminated>() {
+            if let Some(who) = &terminated.who {
+                endpoints.unwatch(who, &target);
+            }
+            Some(SystemMessage::Terminated(terminated.clone()))
+        } else {
+            None
+        };
+        if let Some(system_message) = system_message {
+            process.send_system_message(&target, system_message);
+            continue;
+        }
+        process.send_user_message(&target, MessageEnvelope::new(message, sender, header));
+    }
+    dropped
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actor::{Actor, Props};
+    use crate::context::ActorContext;
+    use crate::message::{MessageHeader, Pid, Touch};
+    use crate::remote::blocklist::BlockReason;
+    use crate::remote::endpoint::EndpointManager;
+    use crate::remote::protos::{remote_message, RemoteMessage};
+    use crate::remote::server::deliver;
+    use crate::remote::tests::{config, hex, new_system, BATCH_FRAME};
+    use async_trait::async_trait;
+    use prost::Message;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+
+    struct Recorder(mpsc::UnboundedSender<(Option<Pid>, MessageHeader)>);
+
+    #[async_trait]
+    impl Actor for Recorder {
+        async fn receive(&mut self, ctx: &mut ActorContext) {
+            if ctx.get_message().is::<Touch>() {
+                let envelope = ctx.get_envelope();
+                let received = (ctx.get_sender().cloned(), envelope.get_header().clone());
+                let _ = self.0.send(received);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn should_deliver_decoded_batches() {
+        let system = new_system();
+        system.set_address("127.0.0.1:9100".to_string());
+        let endpoints = EndpointManager::new(&system, config(0), "rust-system".into());
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let producer = move || Recorder(sender.clone());
+        let props = Props::from_producer(producer);
+        system.root().spawn_named(props, "echo").unwrap();
+        let message = RemoteMessage::decode(hex(BATCH_FRAME).as_slice()).unwrap();
+        let Some(remote_message::MessageType::MessageBatch(batch)) = message.message_type else {
+            panic!("expected message batch");
+        };
+
+        assert_eq!(0, deliver(&endpoints, batch, None));
+
+        let (sender, header) = receiver.recv().await.unwrap();
+        let expected = Pid {
+            request_id: 7,
+            ..Pid::new("127.0.0.1:9000", "watcher")
+        };
+        assert_eq!(Some(expected), sender);
+        assert_eq!(Some(&"go".to_string()), header.get("tenant"));
+    }
+
+    #[tokio::test]
+    async fn should_dead_letter_batches_of_blocked_peers() {
+        let system = new_system();
+        system.set_address("127.0.0.1:9100".to_string());
+        let endpoints = EndpointManager::new(&system, config(0), "rust-system".into());
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let producer = move || Recorder(sender.clone());
+        let props = Props::from_producer(producer);
+        system.root().spawn_named(props, "echo").unwrap();
+        let message = RemoteMessage::decode(hex(BATCH_FRAME).as_slice()).unwrap();
+        let Some(remote_message::MessageType::MessageBatch(batch)) = message.message_type else {
+            panic!("expected message batch");
+        };
+
+        assert_eq!(0, deliver(&endpoints, batch, Some(BlockReason::Blocked)));
+
+        let received = tokio::time::timeout(Duration::from_millis(50), receiver.recv()).await;
+        assert!(received.is_err());
+    }
+}
@@ -0,0 +1,90 @@
+#[cfg(feature = "grpc")]
+mod grpc;
+mod memory;
+mod tcp;
+#[cfg(feature = "tls")]
+mod tls;
+#[cfg(unix)]
+mod unix;
+
+#[cfg(feature = "grpc")]
+pub use grpc::*;
+pub use memory::*;
+pub use tcp::*;
+#[cfg(feature = "tls")]
+pub use tls::*;
+#[cfg(unix)]
+pub use unix::*;
+
+use crate::remote::RemoteConfig;
+use async_trait::async_trait;
+use std::io;
+use tokio::io::{AsyncRead, AsyncWrite, DuplexStream};
+use tokio::net::TcpStream;
+#[cfg(unix)]
+use tokio::net::UnixStream;
+
+/// Bidirectional byte stream between two actor systems.
+pub trait Connection: AsyncRead + AsyncWrite + Send + Unpin {
+    /// DER encoded certificate chain the peer authenticated with, empty if the connection is
+    /// not authenticated.
+    fn peer_certificates(&self) -> Vec<Vec<u8>> {
+        Vec::new()
+    }
+}
+
+impl Connection for TcpStream {}
+
+#[cfg(unix)]
+impl Connection for UnixStream {}
+
+impl Connection for DuplexStream {}
+
+impl Connection for BoxConnection {
+    fn peer_certificates(&self) -> Vec<Vec<u8>> {
+        (**self).peer_certificates()
+    }
+}
+
+pub type BoxConnection = Box<dyn Connection>;
+
+/// Establishes connections between actor systems, [TcpTransport] by default.
+#[async_trait]
+pub trait Transport: Send + Sync {
+    /// Starts accepting connections on the host and port of the config.
+    async fn bind(&self, config: &RemoteConfig) -> io::Result<Box<dyn Listener>>;
+
+    /// Connects to the actor system listening on `address`, on behalf of the actor system
+    /// configured with `config`.
+    async fn connect(&self, address: &str, config: &RemoteConfig) -> io::Result<BoxConnection>;
+
+    /// Whether peers exchange heartbeat messages to detect broken connections. Peers of other
+    /// Proto.Actor implementations do not answer them and rely on keep-alive of the transport.
+    fn uses_heartbeats(&self) -> bool {
+        true
+    }
+}
+
+/// Accepts connections until dropped.
+#[async_trait]
+pub trait Listener: Send {
+    /// Address other actor systems connect to, [Pid](crate::message::Pid)s of the local
+    /// actor system carry it.
+    fn address(&self) -> String;
+
+    /// Waits for the next connection, returns it with the address of the peer.
+    async fn accept(&mut self) -> io::Result<(BoxConnection, String)>;
+}
+
+/// Actor system connecting to the local one, authorized by the peer authorizer of
+/// [RemoteConfig] before any of its messages are accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Peer {
+    /// Address the peer actor system is reachable at, as it announced it.
+    pub address: String,
+    /// Address the connection comes from.
+    pub remote_address: String,
+    /// DER encoded certificate chain of the peer, empty if the transport does not
+    /// authenticate peers.
+    pub certificates: Vec<Vec<u8>>,
+}
@@ -0,0 +1,465 @@
+use crate::remote::frame::{check_frame_size, read_frame_bytes, write_frame_bytes};
+use crate::remote::transport::{BoxConnection, Listener, TcpTransport, Transport};
+use crate::remote::RemoteConfig;
+use async_trait::async_trait;
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use h2::server::SendResponse;
+use h2::{client, server, Ping, PingPong, RecvStream, SendStream};
+use http::header::CONTENT_TYPE;
+use http::{HeaderMap, HeaderValue, Method, Request, Response};
+use log::debug;
+use std::future::Future;
+use std::io;
+use std::time::Duration;
+use tokio::io::DuplexStream;
+use tokio::sync::mpsc;
+use tokio::task::JoinSet;
+
+/// Path of the bidirectional streaming call Proto.Actor implementations exchange
+/// `remote.RemoteMessage`s on.
+pub const REMOTING_RECEIVE: &str = "/remote.Remoting/Receive";
+
+const GRPC_CONTENT_TYPE: &str = "application/grpc";
+const GRPC_STATUS: &str = "grpc-status";
+const GRPC_STATUS_OK: &str = "0";
+const GRPC_STATUS_UNIMPLEMENTED: &str = "12";
+/// Compressed flag and big endian length preceding every gRPC message.
+const GRPC_PREFIX_SIZE: usize = 5;
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// Connects actor systems over the gRPC service of Proto.Actor Go and .NET, on top of another
+/// [Transport], [TcpTransport] by default.
+///
+/// Each connection is a `Receive` call of the `remote.Remoting` service, carrying the same
+/// `remote.RemoteMessage`s as the other transports. Peers of other implementations do not
+/// answer heartbeat messages, so connections are kept alive with HTTP/2 pings instead.
+#[derive(Debug, Clone, Copy)]
+pub struct GrpcTransport<T = TcpTransport> {
+    inner: T,
+    keep_alive: KeepAlive,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct KeepAlive {
+    interval: Duration,
+    timeout: Duration,
+}
+
+impl Default for GrpcTransport {
+    fn default() -> Self {
+        Self::over(TcpTransport)
+    }
+}
+
+impl GrpcTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<T> GrpcTransport<T>
+where
+    T: Transport,
+{
+    /// gRPC over the connections of `inner`, such as a
+    /// [TlsTransport](crate::remote::TlsTransport) with `h2` in its ALPN protocols.
+    pub fn over(inner: T) -> Self {
+        Self {
+            inner,
+            keep_alive: KeepAlive {
+                interval: Duration::from_secs(10),
+                timeout: Duration::from_secs(20),
+            },
+        }
+    }
+
+    /// Sends an HTTP/2 ping every `interval` and drops the connection if it is not answered
+    /// within the `timeout`.
+    pub fn with_keep_alive(self, interval: Duration, timeout: Duration) -> Self {
+        Self {
+            keep_alive: KeepAlive { interval, timeout },
+            ..self
+        }
+    }
+}
+
+#[async_trait]
+impl<T> Transport for GrpcTransport<T>
+where
+    T: Transport,
+{
+    async fn bind(&self, config: &RemoteConfig) -> io::Result<Box<dyn Listener>> {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Ok(Box::new(GrpcListener {
+            inner: self.inner.bind(config).await?,
+            keep_alive: self.keep_alive,
+            max_message_size: config.max_frame_size,
+            connections: JoinSet::new(),
+            sender,
+            receiver,
+        }))
+    }
+
+    async fn connect(&self, address: &str, config: &RemoteConfig) -> io::Result<BoxConnection> {
+        let stream = self.inner.connect(address, config).await?;
+        let (client, mut connection) = client::handshake(stream).await.map_err(io_error)?;
+        let ping_pong = connection.ping_pong();
+        let keep_alive = self.keep_alive;
+        tokio::spawn(async move {
+            tokio::select! {
+                result = connection => debug!("HTTP/2 connection closed: {:?}", result),
+                error = keep_alive.run(ping_pong) => debug!("HTTP/2 connection lost: {}", error),
+            }
+        });
+        let request = Request::builder()
+            .method(Method::POST)
+            .uri(format!("http://{}{}", address, REMOTING_RECEIVE))
+            .header(CONTENT_TYPE, GRPC_CONTENT_TYPE)
+            .header("te", "trailers")
+            .body(())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))?;
+        let mut client = client.ready().await.map_err(io_error)?;
+        let (response, send) = client.send_request(request, false).map_err(io_error)?;
+        let body = async move { Ok(response.await?.into_body()) };
+        let (connection, call) = tokio::io::duplex(BUFFER_SIZE);
+        let max_message_size = config.max_frame_size;
+        tokio::spawn(run_call(call, body, send, None, max_message_size));
+        Ok(Box::new(connection))
+    }
+
+    fn uses_heartbeats(&self) -> bool {
+        false
+    }
+}
+
+impl KeepAlive {
+    /// Pings the peer until it fails to answer, [None] never completes.
+    async fn run(self, ping_pong: Option<PingPong>) -> io::Error {
+        let Some(mut ping_pong) = ping_pong else {
+            return std::future::pending().await;
+        };
+        loop {
+            tokio::time::sleep(self.interval).await;
+            match tokio::time::timeout(self.timeout, ping_pong.ping(Ping::opaque())).await {
+                Ok(Ok(_)) => {}
+                Ok(Err(error)) => return io_error(error),
+                Err(_) => return io::Error::new(io::ErrorKind::TimedOut, "peer missed pings"),
+            }
+        }
+    }
+}
+
+/// Accepts HTTP/2 connections of the inner listener and `Receive` calls on them.
+struct GrpcListener {
+    inner: Box<dyn Listener>,
+    keep_alive: KeepAlive,
+    max_message_size: usize,
+    connections: JoinSet<()>,
+    sender: mpsc::UnboundedSender<(BoxConnection, String)>,
+    receiver: mpsc::UnboundedReceiver<(BoxConnection, String)>,
+}
+
+#[async_trait]
+impl Listener for GrpcListener {
+    fn address(&self) -> String {
+        self.inner.address()
+    }
+
+    async fn accept(&mut self) -> io::Result<(BoxConnection, String)> {
+        loop {
+            tokio::select! {
+                accepted = self.inner.accept() => {
+                    let (stream, peer) = accepted?;
+                    let calls = self.sender.clone();
+                    let keep_alive = self.keep_alive;
+                    let max_message_size = self.max_message_size;
+                    let connection = serve(stream, peer, calls, keep_alive, max_message_size);
+                    self.connections.spawn(connection);
+                }
+                Some(call) = self.receiver.recv() => return Ok(call),
+                Some(_) = self.connections.join_next(), if !self.connections.is_empty() => {}
+            }
+        }
+    }
+}
+
+/// Serves calls of a single HTTP/2 connection until it is closed.
+async fn serve(
+    stream: BoxConnection,
+    peer: String,
+    calls: mpsc::UnboundedSender<(BoxConnection, String)>,
+    keep_alive: KeepAlive,
+    max_message_size: usize,
+) {
+    let mut connection = match server::handshake(stream).await {
+        Ok(connection) => connection,
+        Err(error) => return debug!("HTTP/2 handshake with {} failed: {}", peer, error),
+    };
+    let ping_pong = connection.ping_pong();
+    let keep_alive = keep_alive.run(ping_pong);
+    tokio::pin!(keep_alive);
+    let error = loop {
+        tokio::select! {
+            request = connection.accept() => match request {
+                Some(Ok((request, respond))) => {
+                    let call = start_call(request, respond, &peer, &calls, max_message_size);
+                    if let Err(error) = call {
+                        debug!("Call of {} failed: {}", peer, error);
+                    }
+                }
+                Some(Err(error)) => break io_error(error),
+                None => return,
+            },
+            error = &mut keep_alive => break error,
+        }
+    };
+    debug!("HTTP/2 connection of {} failed: {}", peer, error);
+}
+
+/// Answers `Receive` calls with a connection of the call, other calls as unimplemented.
+fn start_call(
+    request: Request<RecvStream>,
+    mut respond: SendResponse<Bytes>,
+    peer: &str,
+    calls: &mpsc::UnboundedSender<(BoxConnection, String)>,
+    max_message_size: usize,
+) -> Result<(), h2::Error> {
+    let response = Response::builder().header(CONTENT_TYPE, GRPC_CONTENT_TYPE);
+    if request.uri().path() != REMOTING_RECEIVE {
+        let response = response.header(GRPC_STATUS, GRPC_STATUS_UNIMPLEMENTED);
+        respond.send_response(response.body(()).unwrap(), true)?;
+        return Ok(());
+    }
+    let send = respond.send_response(response.body(()).unwrap(), false)?;
+    let mut trailers = HeaderMap::new();
+    trailers.insert(GRPC_STATUS, HeaderValue::from_static(GRPC_STATUS_OK));
+    let body = async move { Ok(request.into_body()) };
+    let (connection, call) = tokio::io::duplex(BUFFER_SIZE);
+    tokio::spawn(run_call(call, body, send, Some(trailers), max_message_size));
+    let _ = calls.send((Box::new(connection), peer.to_string()));
+    Ok(())
+}
+
+/// Translates between length delimited frames of the connection and gRPC messages of the call
+/// until either side closes. The server side ends the call with `trailers`. Call fails once
+/// the peer announces a message over `max_message_size` bytes.
+async fn run_call<F>(
+    call: DuplexStream,
+    body: F,
+    mut send: SendStream<Bytes>,
+    trailers: Option<HeaderMap>,
+    max_message_size: usize,
+) where
+    F: Future<Output = Result<RecvStream, h2::Error>>,
+{
+    let (mut reader, mut writer) = tokio::io::split(call);
+    let outgoing = async {
+        while let Some(frame) = read_frame_bytes(&mut reader, usize::MAX).await? {
+            send_message(&mut send, encode_message(&frame)).await?;
+        }
+        match trailers {
+            Some(trailers) => send.send_trailers(trailers).map_err(io_error)?,
+            None => send.send_data(Bytes::new(), true).map_err(io_error)?,
+        }
+        Ok::<_, io::Error>(())
+    };
+    let incoming = async {
+        let mut body = body.await.map_err(io_error)?;
+        let mut buffer = BytesMut::new();
+        while let Some(data) = body.data().await {
+            let data = data.map_err(io_error)?;
+            let _ = body.flow_control().release_capacity(data.len());
+            buffer.extend_from_slice(&data);
+            while let Some(message) = decode_message(&mut buffer, max_message_size)? {
+                write_frame_bytes(&mut writer, &message).await?;
+            }
+        }
+        Ok::<_, io::Error>(())
+    };
+    let result = tokio::select! {
+        result = outgoing => result,
+        result = incoming => result,
+    };
+    if let Err(error) = result {
+        debug!("gRPC call failed: {}", error);
+    }
+}
+
+/// Sends the message in chunks the flow control window of the peer has room for, waiting for
+/// the peer to open the window as needed.
+async fn send_message(send: &mut SendStream<Bytes>, mut message: Bytes) -> io::Result<()> {
+    while !message.is_empty() {
+        send.reserve_capacity(message.len());
+        let capacity = std::future::poll_fn(|cx| send.poll_capacity(cx))
+            .await
+            .ok_or_else(|| io::Error::new(io::ErrorKind::BrokenPipe, "stream closed"))?
+            .map_err(io_error)?;
+        let chunk = message.split_to(capacity.min(message.len()));
+        send.send_data(chunk, false).map_err(io_error)?;
+    }
+    Ok(())
+}
+
+fn io_error(error: h2::Error) -> io::Error {
+    if error.is_io() {
+        error.into_io().unwrap()
+    } else {
+        io::Error::other(error)
+    }
+}
+
+/// Prefixes the message with the uncompressed flag and its length.
+fn encode_message(message: &[u8]) -> Bytes {
+    let mut buffer = BytesMut::with_capacity(GRPC_PREFIX_SIZE + message.len());
+    buffer.put_u8(0);
+    buffer.put_u32(message.len() as u32);
+    buffer.put_slice(message);
+    buffer.freeze()
+}
+
+/// Takes the next complete message out of the buffer.
+fn decode_message(buffer: &mut BytesMut, max_size: usize) -> io::Result<Option<Vec<u8>>> {
+    if buffer.len() < GRPC_PREFIX_SIZE {
+        return Ok(None);
+    }
+    if buffer[0] != 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "compressed gRPC messages are not supported",
+        ));
+    }
+    let length = u32::from_be_bytes([buffer[1], buffer[2], buffer[3], buffer[4]]) as usize;
+    check_frame_size(length as u64, max_size)?;
+    if buffer.len() < GRPC_PREFIX_SIZE + length {
+        return Ok(None);
+    }
+    buffer.advance(GRPC_PREFIX_SIZE);
+    Ok(Some(buffer.split_to(length).to_vec()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_message, encode_message, GRPC_CONTENT_TYPE, REMOTING_RECEIVE};
+    use crate::actor::Props;
+    use crate::context::SenderContext;
+    use crate::remote::protos::{remote_message, RemoteMessage};
+    use crate::remote::tests::{config, hex, new_system, Echo, Ping, CONNECT_REQUEST_FRAME};
+    use crate::remote::{GrpcTransport, Remote, RemoteConfig};
+    use bytes::BytesMut;
+    use http::header::CONTENT_TYPE;
+    use http::{Method, Request};
+    use prost::Message;
+    use std::time::Duration;
+
+    fn grpc_config() -> RemoteConfig {
+        let transport = GrpcTransport::new()
+            .with_keep_alive(Duration::from_millis(20), Duration::from_millis(100));
+        config(0).with_transport(transport)
+    }
+
+    #[test]
+    fn should_prefix_messages_with_flag_and_length() {
+        let message = hex(CONNECT_REQUEST_FRAME);
+
+        let encoded = encode_message(&message);
+
+        assert_eq!([0, 0, 0, 0, 31], encoded[..5]);
+        let mut buffer = BytesMut::from(&encoded[..20]);
+        assert_eq!(None, decode_message(&mut buffer, 31).unwrap());
+        assert!(decode_message(&mut buffer, 30).is_err());
+        buffer.extend_from_slice(&encoded[20..]);
+        assert_eq!(Some(message), decode_message(&mut buffer, 31).unwrap());
+        assert!(buffer.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_request_actor_over_grpc_without_heartbeats() {
+        let system_a = new_system();
+        let _remote_a = Remote::start(&system_a, grpc_config()).await.unwrap();
+        let system_b = new_system();
+        let remote_b = Remote::start(&system_b, grpc_config()).await.unwrap();
+        let echo = system_a.root().spawn(Props::from_producer(|| Echo));
+        let ping = || Ping {
+            text: "ping".into(),
+        };
+
+        let pong = system_b.root().request_future(&echo, ping()).await.unwrap();
+        // longer than the heartbeat timeout of the config
+        tokio::time::sleep(Duration::from_millis(250)).await;
+        let again = system_b.root().request_future(&echo, ping()).await.unwrap();
+
+        let expected = format!("ping None Some({:?})", remote_b.address());
+        assert_eq!(expected, pong.text);
+        assert_eq!(expected, again.text);
+    }
+
+    #[tokio::test]
+    async fn should_send_messages_larger_than_flow_control_window_over_grpc() {
+        let system_a = new_system();
+        let _remote_a = Remote::start(&system_a, grpc_config()).await.unwrap();
+        let system_b = new_system();
+        let _remote_b = Remote::start(&system_b, grpc_config()).await.unwrap();
+        let echo = system_a.root().spawn(Props::from_producer(|| Echo));
+        // default HTTP/2 window is 65535 bytes
+        let text = "ping".repeat(64 * 1024);
+
+        let pong = system_b
+            .root()
+            .request_future(&echo, Ping { text: text.clone() })
+            .await
+            .unwrap();
+
+        assert!(pong.text.starts_with(&text));
+    }
+
+    /// Connects with a plain HTTP/2 client instead of the transport.
+    #[tokio::test]
+    async fn should_answer_connect_request_of_plain_grpc_client() {
+        let system = new_system();
+        let remote = Remote::start(&system, grpc_config()).await.unwrap();
+        let stream = tokio::net::TcpStream::connect(remote.address())
+            .await
+            .unwrap();
+        let (client, connection) = h2::client::handshake(stream).await.unwrap();
+        tokio::spawn(connection);
+        let request = |path: &str| {
+            Request::builder()
+                .method(Method::POST)
+                .uri(format!("http://{}{}", remote.address(), path))
+                .header(CONTENT_TYPE, GRPC_CONTENT_TYPE)
+                .header("te", "trailers")
+                .body(())
+                .unwrap()
+        };
+        let mut client = client.ready().await.unwrap();
+
+        let (unknown, _) = client
+            .send_request(request("/remote.Remoting/ListProcesses"), true)
+            .unwrap();
+        let unknown = unknown.await.unwrap();
+        assert_eq!("12", unknown.headers()["grpc-status"]);
+
+        let (response, mut send) = client
+            .send_request(request(REMOTING_RECEIVE), false)
+            .unwrap();
+        let frame = encode_message(&hex(CONNECT_REQUEST_FRAME));
+        send.send_data(frame, false).unwrap();
+        let response = response.await.unwrap();
+        assert_eq!(GRPC_CONTENT_TYPE, response.headers()[CONTENT_TYPE]);
+        let mut body = response.into_body();
+        let mut buffer = BytesMut::new();
+        let message = loop {
+            if let Some(message) = decode_message(&mut buffer, usize::MAX).unwrap() {
+                break message;
+            }
+            buffer.extend_from_slice(&body.data().await.unwrap().unwrap());
+        };
+        let message = RemoteMessage::decode(message.as_slice()).unwrap();
+        let Some(remote_message::MessageType::ConnectResponse(response)) = message.message_type
+        else {
+            panic!("expected connect response");
+        };
+        assert!(!response.blocked);
+        assert!(!response.member_id.is_empty());
+    }
+}
@@ -0,0 +1,356 @@
+use crate::remote::frame::{read_frame_bytes, write_frame_bytes};
+use crate::remote::transport::{BoxConnection, Listener, Transport};
+use crate::remote::RemoteConfig;
+use async_trait::async_trait;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncRead, AsyncWrite, DuplexStream};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// Network of actor systems in the same process, connected through in-memory streams.
+///
+/// Latency and frame drops apply to every frame sent over the network, partitions refuse
+/// connections between two addresses and break the existing ones. Drops are decided by a
+/// random generator per direction of each connection, seeded from the seed of the network, the
+/// addresses and the number of earlier connections between them. Runs with the same seed drop
+/// the same frames as long as each pair of addresses connects in the same order.
+#[derive(Clone)]
+pub struct MemoryNetwork {
+    state: Arc<Mutex<NetworkState>>,
+}
+
+struct NetworkState {
+    listeners: HashMap<String, mpsc::UnboundedSender<(BoxConnection, String)>>,
+    links: Vec<Link>,
+    partitions: HashSet<(String, String)>,
+    latency: Duration,
+    drop_rate: f64,
+    seed: u64,
+    /// Number of connections made, by client and server address.
+    connections: HashMap<(String, String), u64>,
+    next_port: u16,
+}
+
+/// Connection between two addresses, forwarding frames in both directions.
+struct Link {
+    addresses: (String, String),
+    task: JoinHandle<()>,
+}
+
+impl Default for MemoryNetwork {
+    fn default() -> Self {
+        Self::with_seed(0)
+    }
+}
+
+impl MemoryNetwork {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(NetworkState {
+                listeners: HashMap::new(),
+                links: Vec::new(),
+                partitions: HashSet::new(),
+                latency: Duration::ZERO,
+                drop_rate: 0.0,
+                seed,
+                connections: HashMap::new(),
+                next_port: 1,
+            })),
+        }
+    }
+
+    /// Transport of a single actor system, port `0` in its config picks a free port.
+    pub fn transport(&self) -> MemoryTransport {
+        MemoryTransport {
+            network: self.clone(),
+            address: Mutex::new(None),
+        }
+    }
+
+    /// Delay of every frame.
+    pub fn set_latency(&self, latency: Duration) {
+        self.state.lock().unwrap().latency = latency;
+    }
+
+    /// Probability from `0.0` to `1.0` that a frame is lost.
+    pub fn set_drop_rate(&self, drop_rate: f64) {
+        self.state.lock().unwrap().drop_rate = drop_rate.clamp(0.0, 1.0);
+    }
+
+    /// Breaks connections between the addresses and refuses new ones until healed.
+    pub fn partition(&self, address: &str, other: &str) {
+        let addresses = pair(address, other);
+        let mut state = self.state.lock().unwrap();
+        state.links.retain(|link| {
+            let broken = link.addresses == addresses;
+            if broken {
+                link.task.abort();
+            }
+            !broken
+        });
+        state.partitions.insert(addresses);
+    }
+
+    pub fn heal(&self, address: &str, other: &str) {
+        let addresses = pair(address, other);
+        self.state.lock().unwrap().partitions.remove(&addresses);
+    }
+
+    /// Latency to apply to the next frame, or [None] if it is dropped.
+    fn next_frame(&self, rng: &mut StdRng) -> Option<Duration> {
+        let state = self.state.lock().unwrap();
+        (!rng.gen_bool(state.drop_rate)).then_some(state.latency)
+    }
+
+    async fn run_link(self, client: DuplexStream, server: DuplexStream, rngs: (StdRng, StdRng)) {
+        let (client_reader, client_writer) = tokio::io::split(client);
+        let (server_reader, server_writer) = tokio::io::split(server);
+        tokio::select! {
+            _ = self.forward(client_reader, server_writer, rngs.0) => {}
+            _ = self.forward(server_reader, client_writer, rngs.1) => {}
+        }
+    }
+
+    /// Forwards frames until the reader is closed, dropping both ends of the link afterwards
+    /// closes the connection for both peers.
+    async fn forward<R, W>(&self, mut reader: R, mut writer: W, mut rng: StdRng) -> io::Result<()>
+    where
+        R: AsyncRead + Unpin,
+        W: AsyncWrite + Unpin,
+    {
+        let (sender, mut receiver) = mpsc::unbounded_channel::<(Instant, Vec<u8>)>();
+        let read = async move {
+            // endpoints on both sides of the link enforce their own frame size limits
+            while let Some(frame) = read_frame_bytes(&mut reader, usize::MAX).await? {
+                if let Some(latency) = self.next_frame(&mut rng) {
+                    let _ = sender.send((Instant::now() + latency, frame));
+                }
+            }
+            Ok::<_, io::Error>(())
+        };
+        let write = async {
+            while let Some((deliver_at, frame)) = receiver.recv().await {
+                tokio::time::sleep_until(deliver_at).await;
+                write_frame_bytes(&mut writer, &frame).await?;
+            }
+            Ok(())
+        };
+        tokio::try_join!(read, write).map(|_| ())
+    }
+}
+
+/// Random generator of frames sent `from` → `to` over their `nth` connection, seeded with
+/// FNV-1a of the addresses so it does not depend on the order of other connections.
+fn link_rng(seed: u64, from: &str, to: &str, nth: u64) -> StdRng {
+    let bytes = from.bytes().chain([0]).chain(to.bytes()).chain([0]);
+    let hash = bytes
+        .chain(nth.to_be_bytes())
+        .fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+            (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+        });
+    StdRng::seed_from_u64(seed ^ hash)
+}
+
+fn pair(address: &str, other: &str) -> (String, String) {
+    if address <= other {
+        (address.to_string(), other.to_string())
+    } else {
+        (other.to_string(), address.to_string())
+    }
+}
+
+/// [Transport] of an actor system connected to a [MemoryNetwork].
+pub struct MemoryTransport {
+    network: MemoryNetwork,
+    address: Mutex<Option<String>>,
+}
+
+#[async_trait]
+impl Transport for MemoryTransport {
+    async fn bind(&self, config: &RemoteConfig) -> io::Result<Box<dyn Listener>> {
+        let mut state = self.network.state.lock().unwrap();
+        let port = match config.port {
+            0 => {
+                state.next_port += 1;
+                state.next_port - 1
+            }
+            port => port,
+        };
+        let host = config.advertised_host.as_ref().unwrap_or(&config.host);
+        let address = format!("{}:{}", host, port);
+        if state.listeners.contains_key(&address) {
+            return Err(io::Error::new(io::ErrorKind::AddrInUse, address));
+        }
+        let (sender, receiver) = mpsc::unbounded_channel();
+        state.listeners.insert(address.clone(), sender);
+        *self.address.lock().unwrap() = Some(address.clone());
+        Ok(Box::new(MemoryListener {
+            network: self.network.clone(),
+            address,
+            receiver,
+        }))
+    }
+
+    async fn connect(&self, address: &str, _config: &RemoteConfig) -> io::Result<BoxConnection> {
+        let local_address = self.address.lock().unwrap().clone().unwrap_or_default();
+        let addresses = pair(&local_address, address);
+        let mut state = self.network.state.lock().unwrap();
+        if state.partitions.contains(&addresses) {
+            return Err(io::Error::new(
+                io::ErrorKind::ConnectionRefused,
+                "partitioned",
+            ));
+        }
+        let seed = state.seed;
+        let nth = state
+            .connections
+            .entry((local_address.clone(), address.to_string()))
+            .or_default();
+        *nth += 1;
+        let rngs = (
+            link_rng(seed, &local_address, address, *nth),
+            link_rng(seed, address, &local_address, *nth),
+        );
+        let refused = || io::Error::from(io::ErrorKind::ConnectionRefused);
+        let listener = state.listeners.get(address).ok_or_else(refused)?;
+        let (client, client_link) = tokio::io::duplex(BUFFER_SIZE);
+        let (server_link, server) = tokio::io::duplex(BUFFER_SIZE);
+        listener
+            .send((Box::new(server), local_address))
+            .map_err(|_| refused())?;
+        let network = self.network.clone();
+        let task = tokio::spawn(network.run_link(client_link, server_link, rngs));
+        state.links.retain(|link| !link.task.is_finished());
+        state.links.push(Link { addresses, task });
+        Ok(Box::new(client))
+    }
+}
+
+struct MemoryListener {
+    network: MemoryNetwork,
+    address: String,
+    receiver: mpsc::UnboundedReceiver<(BoxConnection, String)>,
+}
+
+#[async_trait]
+impl Listener for MemoryListener {
+    fn address(&self) -> String {
+        self.address.clone()
+    }
+
+    async fn accept(&mut self) -> io::Result<(BoxConnection, String)> {
+        self.receiver
+            .recv()
+            .await
+            .ok_or_else(|| io::Error::from(io::ErrorKind::NotConnected))
+    }
+}
+
+impl Drop for MemoryListener {
+    fn drop(&mut self) {
+        let mut state = self.network.state.lock().unwrap();
+        state.listeners.remove(&self.address);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actor::Props;
+    use crate::context::SenderContext;
+    use crate::message::TerminatedReason;
+    use crate::process::RequestError;
+    use crate::remote::tests::{config, new_system, Echo, Ping, Ready, Watcher};
+    use crate::remote::{MemoryNetwork, Remote};
+    use crate::system::config::ActorSystemConfig;
+    use crate::system::ActorSystem;
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::mpsc;
+    use tokio::time::Instant;
+
+    async fn start_remote(network: &MemoryNetwork) -> (Arc<ActorSystem>, Arc<Remote>) {
+        let system = new_system();
+        let config = config(0).with_transport(network.transport());
+        let remote = Remote::start(&system, config).await.unwrap();
+        (system, remote)
+    }
+
+    fn ping() -> Ping {
+        Ping {
+            text: "ping".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_delay_frames_by_latency() {
+        let network = MemoryNetwork::new();
+        let (system_a, _remote_a) = start_remote(&network).await;
+        let (system_b, _remote_b) = start_remote(&network).await;
+        let echo = system_a.root().spawn(Props::from_producer(|| Echo));
+        let latency = Duration::from_millis(30);
+        network.set_latency(latency);
+
+        let started = Instant::now();
+        let pong = system_b.root().request_future(&echo, ping()).await.unwrap();
+
+        assert!(pong.text.starts_with("ping"));
+        assert!(started.elapsed() >= latency * 2);
+    }
+
+    #[tokio::test]
+    async fn should_terminate_partitioned_address_until_healed() {
+        let network = MemoryNetwork::new();
+        let (system_a, remote_a) = start_remote(&network).await;
+        let (system_b, remote_b) = start_remote(&network).await;
+        let echo = system_a.root().spawn(Props::from_producer(|| Echo));
+        let (sender, mut receiver) = mpsc::unbounded_channel();
+        let watched = echo.clone();
+        let producer = move || Watcher(watched.clone(), sender.clone());
+        let watcher = system_b.root().spawn(Props::from_producer(producer));
+        system_b
+            .root()
+            .request_future(&watcher, Ready)
+            .await
+            .unwrap();
+        system_b.root().request_future(&echo, ping()).await.unwrap();
+
+        network.partition(remote_a.address(), remote_b.address());
+        let terminated = tokio::time::timeout(Duration::from_secs(1), receiver.recv()).await;
+        let terminated = terminated.unwrap().unwrap();
+        assert_eq!(Some(echo.clone()), terminated.who);
+        assert_eq!(TerminatedReason::AddressTerminated as i32, terminated.why);
+
+        network.heal(remote_a.address(), remote_b.address());
+        let pong = system_b.root().request_future(&echo, ping()).await.unwrap();
+        assert!(pong.text.starts_with("ping"));
+    }
+
+    #[tokio::test]
+    async fn should_time_out_requests_when_frames_are_dropped() {
+        let network = MemoryNetwork::with_seed(7);
+        let (system_a, _remote_a) = start_remote(&network).await;
+        let system_b = ActorSystem::with_config(
+            ActorSystemConfig::setup().with_actor_request_timeout(Duration::from_millis(50)),
+        );
+        let config = config(0).with_transport(network.transport());
+        let _remote_b = Remote::start(&system_b, config).await.unwrap();
+        let echo = system_a.root().spawn(Props::from_producer(|| Echo));
+        network.set_drop_rate(1.0);
+
+        let response = system_b.root().request_future(&echo, ping()).await;
+
+        assert_eq!(Err(RequestError::Timeout), response.map(|_| ()));
+    }
+}
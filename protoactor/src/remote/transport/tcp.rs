@@ -0,0 +1,43 @@
+use crate::remote::transport::{BoxConnection, Listener, Transport};
+use crate::remote::RemoteConfig;
+use async_trait::async_trait;
+use std::io;
+use tokio::net::{TcpListener, TcpStream};
+
+/// Connects actor systems over TCP, addresses are `host:port`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TcpTransport;
+
+#[async_trait]
+impl Transport for TcpTransport {
+    async fn bind(&self, config: &RemoteConfig) -> io::Result<Box<dyn Listener>> {
+        let listener = TcpListener::bind((config.host.as_str(), config.port)).await?;
+        let host = config.advertised_host.as_ref().unwrap_or(&config.host);
+        let address = format!("{}:{}", host, listener.local_addr()?.port());
+        Ok(Box::new(TcpTransportListener { listener, address }))
+    }
+
+    async fn connect(&self, address: &str, _config: &RemoteConfig) -> io::Result<BoxConnection> {
+        let stream = TcpStream::connect(address).await?;
+        stream.set_nodelay(true)?;
+        Ok(Box::new(stream))
+    }
+}
+
+struct TcpTransportListener {
+    listener: TcpListener,
+    address: String,
+}
+
+#[async_trait]
+impl Listener for TcpTransportListener {
+    fn address(&self) -> String {
+        self.address.clone()
+    }
+
+    async fn accept(&mut self) -> io::Result<(BoxConnection, String)> {
+        let (stream, peer) = self.listener.accept().await?;
+        stream.set_nodelay(true)?;
+        Ok((Box::new(stream), peer.to_string()))
+    }
+}
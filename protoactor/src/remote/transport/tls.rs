@@ -0,0 +1,316 @@
+use crate::remote::transport::{BoxConnection, Connection, Listener, TcpTransport, Transport};
+use crate::remote::RemoteConfig;
+use async_trait::async_trait;
+use rustls::crypto::ring;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use rustls::server::WebPkiClientVerifier;
+use rustls::{ClientConfig, RootCertStore, ServerConfig};
+use std::io;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinSet;
+use tokio_rustls::{client, server, TlsAcceptor, TlsConnector};
+
+/// Encrypts connections of another [Transport], [TcpTransport] by default, with TLS.
+///
+/// Both sides of a connection verify the certificate of the other one, peers without a
+/// certificate trusted by the server config are refused during the handshake. The certificate
+/// chain of a peer is passed to the peer authorizer of [RemoteConfig].
+pub struct TlsTransport<T = TcpTransport> {
+    inner: T,
+    acceptor: TlsAcceptor,
+    connector: TlsConnector,
+    server_name: Option<ServerName<'static>>,
+}
+
+impl TlsTransport {
+    /// TLS over TCP with the rustls configs of accepted and initiated connections.
+    pub fn new(server: Arc<ServerConfig>, client: Arc<ClientConfig>) -> Self {
+        Self::over(TcpTransport, server, client)
+    }
+
+    /// Mutual TLS over TCP, actor systems present `certificates` signed by one of `roots` to
+    /// each other.
+    pub fn mutual(
+        certificates: Vec<CertificateDer<'static>>,
+        key: PrivateKeyDer<'static>,
+        roots: RootCertStore,
+    ) -> Result<Self, rustls::Error> {
+        let provider = Arc::new(ring::default_provider());
+        let roots = Arc::new(roots);
+        let verifier = WebPkiClientVerifier::builder_with_provider(roots.clone(), provider.clone())
+            .build()
+            .map_err(|error| rustls::Error::General(error.to_string()))?;
+        let server = ServerConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()?
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(certificates.clone(), key.clone_key())?;
+        let client = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()?
+            .with_root_certificates(roots)
+            .with_client_auth_cert(certificates, key)?;
+        Ok(Self::new(Arc::new(server), Arc::new(client)))
+    }
+}
+
+impl<T> TlsTransport<T>
+where
+    T: Transport,
+{
+    /// TLS over the connections of `inner`.
+    pub fn over(inner: T, server: Arc<ServerConfig>, client: Arc<ClientConfig>) -> Self {
+        Self {
+            inner,
+            acceptor: TlsAcceptor::from(server),
+            connector: TlsConnector::from(client),
+            server_name: None,
+        }
+    }
+
+    /// Name the certificates of all peers are verified against. By default it is the host of
+    /// the address connected to.
+    pub fn with_server_name(self, server_name: ServerName<'static>) -> Self {
+        Self {
+            server_name: Some(server_name),
+            ..self
+        }
+    }
+
+    fn get_server_name(&self, address: &str) -> io::Result<ServerName<'static>> {
+        if let Some(server_name) = &self.server_name {
+            return Ok(server_name.clone());
+        }
+        let host = address.rsplit_once(':').map_or(address, |(host, _)| host);
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        ServerName::try_from(host.to_string())
+            .map_err(|error| io::Error::new(io::ErrorKind::InvalidInput, error))
+    }
+}
+
+#[async_trait]
+impl<T> Transport for TlsTransport<T>
+where
+    T: Transport,
+{
+    async fn bind(&self, config: &RemoteConfig) -> io::Result<Box<dyn Listener>> {
+        Ok(Box::new(TlsListener {
+            inner: self.inner.bind(config).await?,
+            acceptor: self.acceptor.clone(),
+            handshake_timeout: config.connect_timeout,
+            handshakes: JoinSet::new(),
+        }))
+    }
+
+    async fn connect(&self, address: &str, config: &RemoteConfig) -> io::Result<BoxConnection> {
+        let server_name = self.get_server_name(address)?;
+        let stream = self.inner.connect(address, config).await?;
+        let stream = self.connector.connect(server_name, stream).await?;
+        Ok(Box::new(stream))
+    }
+
+    fn uses_heartbeats(&self) -> bool {
+        self.inner.uses_heartbeats()
+    }
+}
+
+/// Accepts connections of the inner listener, handshakes run concurrently so that a slow peer
+/// does not hold up the others.
+struct TlsListener {
+    inner: Box<dyn Listener>,
+    acceptor: TlsAcceptor,
+    handshake_timeout: Duration,
+    handshakes: JoinSet<io::Result<(BoxConnection, String)>>,
+}
+
+#[async_trait]
+impl Listener for TlsListener {
+    fn address(&self) -> String {
+        self.inner.address()
+    }
+
+    async fn accept(&mut self) -> io::Result<(BoxConnection, String)> {
+        loop {
+            tokio::select! {
+                accepted = self.inner.accept() => {
+                    let (stream, peer) = accepted?;
+                    let acceptor = self.acceptor.clone();
+                    let timeout = self.handshake_timeout;
+                    self.handshakes.spawn(handshake(acceptor, stream, peer, timeout));
+                }
+                Some(handshake) = self.handshakes.join_next(), if !self.handshakes.is_empty() => {
+                    return handshake.map_err(io::Error::other)?;
+                }
+            }
+        }
+    }
+}
+
+async fn handshake(
+    acceptor: TlsAcceptor,
+    stream: BoxConnection,
+    peer: String,
+    timeout: Duration,
+) -> io::Result<(BoxConnection, String)> {
+    let stream = tokio::time::timeout(timeout, acceptor.accept(stream))
+        .await
+        .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "handshake timed out"))?
+        .map_err(|error| {
+            let message = format!("handshake with {} failed: {}", peer, error);
+            io::Error::new(error.kind(), message)
+        })?;
+    Ok((Box::new(stream), peer))
+}
+
+fn to_der(certificates: Option<&[CertificateDer<'_>]>) -> Vec<Vec<u8>> {
+    certificates
+        .unwrap_or_default()
+        .iter()
+        .map(|certificate| certificate.to_vec())
+        .collect()
+}
+
+impl<S> Connection for server::TlsStream<S>
+where
+    S: Connection,
+{
+    fn peer_certificates(&self) -> Vec<Vec<u8>> {
+        to_der(self.get_ref().1.peer_certificates())
+    }
+}
+
+impl<S> Connection for client::TlsStream<S>
+where
+    S: Connection,
+{
+    fn peer_certificates(&self) -> Vec<Vec<u8>> {
+        to_der(self.get_ref().1.peer_certificates())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actor::Props;
+    use crate::context::SenderContext;
+    use crate::message::Pid;
+    use crate::process::RequestError;
+    use crate::remote::tests::{config, new_system, Echo, Ping, Pong};
+    use crate::remote::{Remote, TlsTransport};
+    use rcgen::{BasicConstraints, CertificateParams, IsCa, KeyPair};
+    use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer};
+    use rustls::RootCertStore;
+    use std::sync::{Arc, Mutex};
+
+    struct Authority {
+        certificate: rcgen::Certificate,
+        key: KeyPair,
+    }
+
+    impl Authority {
+        fn new() -> Self {
+            let mut params = CertificateParams::new(Vec::new()).unwrap();
+            params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            let key = KeyPair::generate().unwrap();
+            let certificate = params.self_signed(&key).unwrap();
+            Self { certificate, key }
+        }
+
+        /// Issues a certificate for 127.0.0.1 and its key.
+        fn certify(&self) -> (CertificateDer<'static>, PrivateKeyDer<'static>) {
+            let params = CertificateParams::new(vec!["127.0.0.1".to_string()]).unwrap();
+            let key = KeyPair::generate().unwrap();
+            let certificate = params
+                .signed_by(&key, &self.certificate, &self.key)
+                .unwrap();
+            let key = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(key.serialize_der()));
+            (certificate.der().clone(), key)
+        }
+
+        fn transport(&self) -> TlsTransport {
+            self.transport_of(&self.certify())
+        }
+
+        fn transport_of(
+            &self,
+            (certificate, key): &(CertificateDer<'static>, PrivateKeyDer<'static>),
+        ) -> TlsTransport {
+            let mut roots = RootCertStore::empty();
+            roots.add(self.certificate.der().clone()).unwrap();
+            TlsTransport::mutual(vec![certificate.clone()], key.clone_key(), roots).unwrap()
+        }
+    }
+
+    fn ping() -> Ping {
+        Ping {
+            text: "ping".into(),
+        }
+    }
+
+    #[tokio::test]
+    async fn should_authenticate_peers_with_certificates() {
+        let authority = Authority::new();
+        let peers = Arc::new(Mutex::new(Vec::new()));
+        let authorized = peers.clone();
+        let config_a = config(0)
+            .with_transport(authority.transport())
+            .with_peer_authorizer(move |peer| {
+                authorized.lock().unwrap().push(peer.clone());
+                true
+            });
+        let system_a = new_system();
+        let _remote_a = Remote::start(&system_a, config_a).await.unwrap();
+        let system_b = new_system();
+        let config_b = config(0).with_transport(authority.transport());
+        let remote_b = Remote::start(&system_b, config_b).await.unwrap();
+        let echo = system_a.root().spawn(Props::from_producer(|| Echo));
+
+        let pong = system_b.root().request_future(&echo, ping()).await;
+
+        assert!(pong.unwrap().text.starts_with("ping"));
+        let peers = peers.lock().unwrap();
+        assert_eq!(remote_b.address(), peers[0].address);
+        assert_eq!(1, peers[0].certificates.len());
+    }
+
+    #[tokio::test]
+    async fn should_refuse_peers_of_untrusted_authority() {
+        let system_a = new_system();
+        let config_a = config(0).with_transport(Authority::new().transport());
+        let remote_a = Remote::start(&system_a, config_a).await.unwrap();
+        let system_b = new_system();
+        let config_b = config(0).with_transport(Authority::new().transport());
+        let _remote_b = Remote::start(&system_b, config_b).await.unwrap();
+        let target = Pid::new(remote_a.address(), "echo");
+
+        let response = system_b.root().request_future(&target, ping()).await;
+
+        let expected: Result<Pong, _> = Err(RequestError::DeadLetter(Some(target)));
+        assert_eq!(expected, response);
+    }
+
+    #[tokio::test]
+    async fn should_refuse_certificate_of_blocked_peer_announcing_another_address() {
+        let authority = Authority::new();
+        let system_a = new_system();
+        let config_a = config(0).with_transport(authority.transport());
+        let remote_a = Remote::start(&system_a, config_a).await.unwrap();
+        let identity = authority.certify();
+        let system_b = new_system();
+        let config_b = config(0).with_transport(authority.transport_of(&identity));
+        let remote_b = Remote::start(&system_b, config_b).await.unwrap();
+        let echo = system_a.root().spawn(Props::from_producer(|| Echo));
+        system_b.root().request_future(&echo, ping()).await.unwrap();
+
+        remote_a.block(remote_b.address());
+
+        let system_c = new_system();
+        let config_c = config(0).with_transport(authority.transport_of(&identity));
+        let _remote_c = Remote::start(&system_c, config_c).await.unwrap();
+        let response = system_c.root().request_future(&echo, ping()).await;
+        let expected: Result<Pong, _> = Err(RequestError::DeadLetter(Some(echo.clone())));
+        assert_eq!(expected, response);
+
+        remote_a.unblock(remote_b.address());
+        let pong = system_c.root().request_future(&echo, ping()).await;
+        assert!(pong.unwrap().text.starts_with("ping"));
+    }
+}
@@ -0,0 +1,122 @@
+use crate::remote::transport::{BoxConnection, Listener, Transport};
+use crate::remote::RemoteConfig;
+use async_trait::async_trait;
+use std::io;
+use std::path::PathBuf;
+use tokio::net::{UnixListener, UnixStream};
+
+/// Prefix of Unix domain socket addresses.
+pub const UNIX_SCHEME: &str = "unix:";
+
+/// Connects actor systems on the same host over Unix domain sockets, addresses are
+/// `unix:/path/to/socket`. The host of [RemoteConfig] is the address to bind, its port is
+/// ignored.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UnixTransport;
+
+fn socket_path(address: &str) -> io::Result<PathBuf> {
+    match address.strip_prefix(UNIX_SCHEME) {
+        Some(path) if !path.is_empty() => Ok(PathBuf::from(path)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("{} is not a unix socket address", address),
+        )),
+    }
+}
+
+#[async_trait]
+impl Transport for UnixTransport {
+    async fn bind(&self, config: &RemoteConfig) -> io::Result<Box<dyn Listener>> {
+        let path = socket_path(&config.host)?;
+        let listener = match UnixListener::bind(&path) {
+            Err(error) if error.kind() == io::ErrorKind::AddrInUse => {
+                // socket file left behind by a process that did not shut down cleanly
+                if UnixStream::connect(&path).await.is_ok() {
+                    return Err(error);
+                }
+                std::fs::remove_file(&path)?;
+                UnixListener::bind(&path)?
+            }
+            listener => listener?,
+        };
+        Ok(Box::new(UnixTransportListener {
+            listener,
+            address: format!("{}{}", UNIX_SCHEME, path.display()),
+            path,
+        }))
+    }
+
+    async fn connect(&self, address: &str, _config: &RemoteConfig) -> io::Result<BoxConnection> {
+        let stream = UnixStream::connect(socket_path(address)?).await?;
+        Ok(Box::new(stream))
+    }
+}
+
+/// Removes the socket file once dropped.
+struct UnixTransportListener {
+    listener: UnixListener,
+    address: String,
+    path: PathBuf,
+}
+
+#[async_trait]
+impl Listener for UnixTransportListener {
+    fn address(&self) -> String {
+        self.address.clone()
+    }
+
+    async fn accept(&mut self) -> io::Result<(BoxConnection, String)> {
+        let (stream, peer) = self.listener.accept().await?;
+        let peer = match peer.as_pathname() {
+            Some(path) => format!("{}{}", UNIX_SCHEME, path.display()),
+            None => UNIX_SCHEME.to_string(),
+        };
+        Ok((Box::new(stream), peer))
+    }
+}
+
+impl Drop for UnixTransportListener {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actor::Props;
+    use crate::context::SenderContext;
+    use crate::remote::tests::{configure, new_system, Echo, Ping};
+    use crate::remote::{Remote, RemoteConfig};
+    use std::path::PathBuf;
+
+    fn socket_path(name: &str) -> PathBuf {
+        let name = format!("protoactor-{}-{}.sock", std::process::id(), name);
+        std::env::temp_dir().join(name)
+    }
+
+    #[tokio::test]
+    async fn should_request_actor_over_unix_socket() {
+        let path_a = socket_path("a");
+        let path_b = socket_path("b");
+        // socket file of a listener that is gone
+        drop(std::os::unix::net::UnixListener::bind(&path_a).unwrap());
+        let system_a = new_system();
+        let config_a = configure(RemoteConfig::bind_unix(&path_a));
+        let _remote_a = Remote::start(&system_a, config_a).await.unwrap();
+        let system_b = new_system();
+        let config_b = configure(RemoteConfig::bind_unix(&path_b));
+        let remote_b = Remote::start(&system_b, config_b).await.unwrap();
+        let echo = system_a.root().spawn(Props::from_producer(|| Echo));
+        assert_eq!(format!("unix:{}", path_a.display()), echo.address);
+
+        let ping = Ping {
+            text: "ping".into(),
+        };
+        let pong = system_b.root().request_future(&echo, ping).await.unwrap();
+
+        let expected = format!("ping None Some({:?})", remote_b.address());
+        assert_eq!(expected, pong.text);
+        let in_use = Remote::start(&new_system(), RemoteConfig::bind_unix(&path_a)).await;
+        assert_eq!(std::io::ErrorKind::AddrInUse, in_use.err().unwrap().kind());
+    }
+}
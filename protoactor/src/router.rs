@@ -0,0 +1,255 @@
+//! Routers distribute messages sent to a single [Pid](crate::message::Pid) across routees.
+//!
+//! Pool router spawns its routees from [Props](crate::actor::Props) as its children, group
+//! router routes to a fixed list of existing processes. Messages are routed directly by the
+//! router process, without passing through the router mailbox, according to the
+//! [RoutingStrategy]. [AddRoutee], [RemoveRoutee], [GetRoutees] and
+//! [PoisonPill](crate::message::PoisonPill) are handled by the router itself.
+//!
+//! ```
+//! use protoactor::actor::{Actor, Props};
+//! use protoactor::context::ActorContext;
+//! use protoactor::router::{RouterConfig, RoutingStrategy};
+//! use protoactor::system::ActorSystem;
+//! # use async_trait::async_trait;
+//! # struct Worker;
+//! # #[async_trait]
+//! # impl Actor for Worker {
+//! #     async fn receive(&mut self, _: &mut ActorContext) {}
+//! # }
+//!
+//! # #[tokio::main(flavor = "current_thread")]
+//! # async fn main() {
+//! let system = ActorSystem::new();
+//! let props = RouterConfig::pool(RoutingStrategy::RoundRobin, 4, Props::from_producer(|| Worker));
+//! let router = system.root().spawn(props.into_props());
+//! # }
+//! ```
+mod config;
+mod messages;
+mod resizer;
+mod router_actor;
+mod router_process;
+mod router_state;
+
+pub use config::*;
+pub use messages::*;
+pub use resizer::*;
+
+#[cfg(test)]
+mod tests {
+    use crate::actor::{Actor, Props};
+    use crate::context::{ActorContext, SenderContext};
+    use crate::message::{Message, MessageHeader, Pid};
+    use crate::router::{
+        AddRoutee, GetRoutees, HashKey, Hashable, RemoveRoutee, Resizer, RouterConfig,
+        RoutingStrategy,
+    };
+    use crate::system::ActorSystem;
+    use async_trait::async_trait;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    struct Work(&'static str);
+
+    struct Handled(Pid);
+
+    impl Message for Work {
+        type Result = Handled;
+    }
+
+    impl Message for Handled {
+        type Result = ();
+    }
+
+    impl Hashable for Work {
+        fn hash_key(&self) -> String {
+            self.0.to_string()
+        }
+    }
+
+    struct Worker(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl Actor for Worker {
+        async fn receive(&mut self, ctx: &mut ActorContext) {
+            if ctx.get_message().is::<Work>() {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                ctx.respond(Handled(ctx.get_self().clone()));
+            }
+        }
+    }
+
+    fn spawn_workers(system: &Arc<ActorSystem>, count: usize) -> (Vec<Pid>, Arc<AtomicUsize>) {
+        let received = Arc::new(AtomicUsize::new(0));
+        let workers = (0..count)
+            .map(|_| {
+                let received = received.clone();
+                system
+                    .root()
+                    .spawn(Props::from_producer(move || Worker(received.clone())))
+            })
+            .collect();
+        (workers, received)
+    }
+
+    async fn handled_by(system: &Arc<ActorSystem>, router: &Pid, work: Work) -> Pid {
+        system.root().request_future(router, work).await.unwrap().0
+    }
+
+    #[tokio::test]
+    async fn should_route_round_robin_to_group() {
+        let system = ActorSystem::new();
+        let (workers, _) = spawn_workers(&system, 3);
+        let config = RouterConfig::group(RoutingStrategy::RoundRobin, workers.clone());
+        let router = system.root().spawn(config.into_props());
+
+        let mut handled = Vec::new();
+        for _ in 0..6 {
+            handled.push(handled_by(&system, &router, Work("")).await);
+        }
+
+        assert_eq!(handled[..3], handled[3..]);
+        for worker in &workers {
+            assert!(handled[..3].contains(worker));
+        }
+    }
+
+    #[tokio::test]
+    async fn should_route_randomly_to_routees() {
+        let system = ActorSystem::new();
+        let (workers, _) = spawn_workers(&system, 3);
+        let config = RouterConfig::group(RoutingStrategy::Random, workers.clone());
+        let router = system.root().spawn(config.into_props());
+
+        for _ in 0..10 {
+            assert!(workers.contains(&handled_by(&system, &router, Work("")).await));
+        }
+    }
+
+    #[tokio::test]
+    async fn should_broadcast_to_all_routees() {
+        let system = ActorSystem::new();
+        let (workers, received) = spawn_workers(&system, 3);
+        let config = RouterConfig::group(RoutingStrategy::Broadcast, workers.clone());
+        let router = system.root().spawn(config.into_props());
+
+        system.root().send(&router, Work(""));
+        for worker in &workers {
+            handled_by(&system, worker, Work("")).await;
+        }
+
+        assert_eq!(6, received.load(Ordering::SeqCst));
+    }
+
+    #[tokio::test]
+    async fn should_route_by_consistent_hash_of_message_or_header() {
+        let system = ActorSystem::new();
+        let (workers, _) = spawn_workers(&system, 5);
+        let key = HashKey::header("tenant").or_message::<Work>();
+        let config = RouterConfig::group(RoutingStrategy::ConsistentHash(key), workers);
+        let router = system.root().spawn(config.into_props());
+
+        let mut by_key = HashMap::new();
+        for key in ["a", "b", "c", "d", "e", "f", "a", "b", "c", "d", "e", "f"] {
+            let handler = handled_by(&system, &router, Work(key)).await;
+            assert_eq!(
+                &handler,
+                by_key.entry(key).or_insert_with(|| handler.clone())
+            );
+        }
+        let header: MessageHeader = [("tenant".to_string(), "a".to_string())].into();
+        let by_header = system
+            .root()
+            .with_headers(header)
+            .request_future(&router, Work("b"))
+            .await
+            .unwrap();
+
+        assert_eq!(by_key["a"], by_header.0);
+    }
+
+    #[tokio::test]
+    async fn should_manage_pool_routees() {
+        let system = ActorSystem::new();
+        let received = Arc::new(AtomicUsize::new(0));
+        let worker = Props::from_producer(move || Worker(received.clone()));
+        let config = RouterConfig::pool(RoutingStrategy::RoundRobin, 3, worker);
+        let router = system.root().spawn(config.into_props());
+        let root = system.root();
+
+        let routees = root.request_future(&router, GetRoutees).await.unwrap().pids;
+        assert_eq!(3, routees.len());
+        root.send(
+            &router,
+            RemoveRoutee {
+                pid: routees[0].clone(),
+            },
+        );
+        let (extra, _) = spawn_workers(&system, 1);
+        root.send(
+            &router,
+            AddRoutee {
+                pid: extra[0].clone(),
+            },
+        );
+        let updated = root.request_future(&router, GetRoutees).await.unwrap().pids;
+        assert_eq!(routees[1..], updated[..2]);
+        assert_eq!(extra[0], updated[2]);
+
+        root.stop(&router);
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(system.registry().get(&router).is_none());
+        assert!(system.registry().get(&routees[1]).is_none());
+        assert!(system.registry().get(&extra[0]).is_some());
+    }
+
+    struct SlowWork;
+
+    impl Message for SlowWork {
+        type Result = ();
+    }
+
+    struct SlowWorker;
+
+    #[async_trait]
+    impl Actor for SlowWorker {
+        async fn receive(&mut self, ctx: &mut ActorContext) {
+            if ctx.get_message().is::<SlowWork>() {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        }
+    }
+
+    async fn wait_for_routees(system: &Arc<ActorSystem>, router: &Pid, count: usize) {
+        for _ in 0..200 {
+            let routees = system.root().request_future(router, GetRoutees).await;
+            if routees.unwrap().pids.len() == count {
+                return;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        panic!("router did not resize to {} routees", count);
+    }
+
+    #[tokio::test]
+    async fn should_resize_pool_with_load() {
+        let system = ActorSystem::new();
+        let resizer = Resizer::new(1, 3)
+            .with_window(Duration::from_millis(20))
+            .with_step(2);
+        let worker = Props::from_producer(|| SlowWorker);
+        let config =
+            RouterConfig::pool(RoutingStrategy::RoundRobin, 0, worker).with_resizer(resizer);
+        let router = system.root().spawn(config.into_props());
+
+        wait_for_routees(&system, &router, 1).await;
+        for _ in 0..30 {
+            system.root().send(&router, SlowWork);
+        }
+        wait_for_routees(&system, &router, 3).await;
+        wait_for_routees(&system, &router, 1).await;
+    }
+}
@@ -0,0 +1,186 @@
+use crate::actor::Props;
+use crate::message::{AnyMessage, MessageEnvelope, Pid};
+use crate::router::router_actor::RouterActor;
+use crate::router::router_process::RouterProcess;
+use crate::router::router_state::RouterState;
+use crate::router::Resizer;
+use crate::system::ActorSystem;
+use log::warn;
+use std::sync::Arc;
+
+/// Implemented by messages that carry their own key for [RoutingStrategy::ConsistentHash].
+pub trait Hashable {
+    fn hash_key(&self) -> String;
+}
+
+type KeyExtractor = Arc<dyn Fn(&MessageEnvelope<AnyMessage>) -> Option<String> + Send + Sync>;
+
+/// Where [RoutingStrategy::ConsistentHash] takes the key of the message from.
+///
+/// Sources are tried in the order they were added, messages without a key are sent to dead
+/// letters.
+#[derive(Clone)]
+pub struct HashKey {
+    extractors: Vec<KeyExtractor>,
+}
+
+impl HashKey {
+    /// Key is the value of the [MessageHeader](crate::message::MessageHeader) entry.
+    pub fn header<K>(key: K) -> Self
+    where
+        K: Into<String>,
+    {
+        Self {
+            extractors: Vec::new(),
+        }
+        .or_header(key)
+    }
+
+    /// Key is [Hashable::hash_key] of messages of type `M`.
+    pub fn message<M>() -> Self
+    where
+        M: Hashable + Send + Sync + 'static,
+    {
+        Self {
+            extractors: Vec::new(),
+        }
+        .or_message::<M>()
+    }
+
+    pub fn or_header<K>(mut self, key: K) -> Self
+    where
+        K: Into<String>,
+    {
+        let key = key.into();
+        self.extractors.push(Arc::new(move |envelope| {
+            envelope.get_header().get(&key).cloned()
+        }));
+        self
+    }
+
+    pub fn or_message<M>(mut self) -> Self
+    where
+        M: Hashable + Send + Sync + 'static,
+    {
+        self.extractors.push(Arc::new(|envelope| {
+            envelope
+                .get_message()
+                .downcast_ref::<M>()
+                .map(Hashable::hash_key)
+        }));
+        self
+    }
+
+    pub(crate) fn get(&self, envelope: &MessageEnvelope<AnyMessage>) -> Option<String> {
+        self.extractors
+            .iter()
+            .find_map(|extractor| extractor(envelope))
+    }
+}
+
+/// How router picks routees for a message.
+#[derive(Clone)]
+pub enum RoutingStrategy {
+    /// Each message goes to the next routee in turn.
+    RoundRobin,
+    /// Each message goes to a randomly chosen routee.
+    Random,
+    /// Each message goes to all routees.
+    Broadcast,
+    /// Messages with the same key go to the same routee while the routees do not change,
+    /// adding or removing a routee remaps only a fraction of the keys.
+    ConsistentHash(HashKey),
+}
+
+#[derive(Clone)]
+pub(crate) enum RouterKind {
+    Pool { props: Props, size: usize },
+    Group { routees: Vec<Pid> },
+}
+
+/// Configuration of a router, turned into [Props] with [RouterConfig::into_props].
+#[derive(Clone)]
+pub struct RouterConfig {
+    pub(crate) strategy: RoutingStrategy,
+    pub(crate) kind: RouterKind,
+    pub(crate) resizer: Option<Resizer>,
+}
+
+impl RouterConfig {
+    /// Router that spawns `size` routees from `props` as its children.
+    pub fn pool(strategy: RoutingStrategy, size: usize, props: Props) -> Self {
+        Self {
+            strategy,
+            kind: RouterKind::Pool { props, size },
+            resizer: None,
+        }
+    }
+
+    /// Router that routes to existing processes, routees that stop are removed.
+    pub fn group<I>(strategy: RoutingStrategy, routees: I) -> Self
+    where
+        I: IntoIterator<Item = Pid>,
+    {
+        Self {
+            strategy,
+            kind: RouterKind::Group {
+                routees: routees.into_iter().collect(),
+            },
+            resizer: None,
+        }
+    }
+
+    /// Resizes pool routees based on their load, initial size is clamped to the resizer
+    /// bounds. Group routers do not resize.
+    pub fn with_resizer(self, resizer: Resizer) -> Self {
+        Self {
+            resizer: Some(resizer),
+            ..self
+        }
+    }
+
+    /// Props that spawn the router.
+    pub fn into_props(self) -> Props {
+        // spawner replaces the producer with one sharing the state with the router process
+        let config = self.clone();
+        Props::from_producer(move || RouterActor::new(config.clone(), RouterState::new(&config)))
+            .with_spawner(Arc::new(move |props, system, name, parent| {
+                let state = RouterState::new(&self);
+                let (config, actor_state) = (self.clone(), state.clone());
+                let pid = props
+                    .with_producer(move || RouterActor::new(config.clone(), actor_state.clone()))
+                    .spawn_process(system, name, parent, |process| {
+                        Arc::new(RouterProcess::new(process, state.clone()))
+                    })?;
+                self.add_routees(system, &pid, &state);
+                Ok(pid)
+            }))
+    }
+
+    /// Adds routees before the router is returned to the caller, so that messages sent right
+    /// after spawning it are routed.
+    fn add_routees(&self, system: &Arc<ActorSystem>, router: &Pid, state: &RouterState) {
+        match &self.kind {
+            RouterKind::Pool { props, size } => {
+                let size = match &self.resizer {
+                    Some(resizer) => resizer.clamp(*size),
+                    None => *size,
+                };
+                for _ in 0..size {
+                    let name = format!("{}/{}", router.id, system.registry().next_id());
+                    match props.spawn(system, name, Some(router.clone())) {
+                        Ok(routee) => {
+                            state.add(routee);
+                        }
+                        Err(error) => warn!("Failed to spawn routee of {}: {}", router, error),
+                    }
+                }
+            }
+            RouterKind::Group { routees } => {
+                for routee in routees {
+                    state.add(routee.clone());
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use crate::message::{Message, Pid};
+
+/// Adds the process to the routees of the router.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AddRoutee {
+    pub pid: Pid,
+}
+
+/// Removes the process from the routees of the router, pool routers also stop it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemoveRoutee {
+    pub pid: Pid,
+}
+
+/// Asks the router for its current [Routees].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetRoutees;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Routees {
+    pub pids: Vec<Pid>,
+}
+
+impl Message for AddRoutee {
+    type Result = ();
+}
+
+impl Message for RemoveRoutee {
+    type Result = ();
+}
+
+impl Message for GetRoutees {
+    type Result = Routees;
+}
+
+impl Message for Routees {
+    type Result = ();
+}
@@ -0,0 +1,107 @@
+use std::time::Duration;
+
+/// Grows or shrinks pool router routees between bounds, see [RouterConfig::with_resizer].
+///
+/// At the end of every window the pool grows when the average routee mailbox length reaches
+/// the grow pressure, and shrinks when mailboxes are empty and routees handled fewer messages
+/// per second than the shrink throughput.
+///
+/// [RouterConfig::with_resizer]: crate::router::RouterConfig::with_resizer
+#[derive(Debug, Clone, PartialEq)]
+pub struct Resizer {
+    pub(crate) lower_bound: usize,
+    pub(crate) upper_bound: usize,
+    pub(crate) window: Duration,
+    pub(crate) grow_pressure: f64,
+    pub(crate) shrink_throughput: f64,
+    pub(crate) step: usize,
+}
+
+impl Resizer {
+    pub fn new(lower_bound: usize, upper_bound: usize) -> Self {
+        Self {
+            lower_bound,
+            upper_bound: upper_bound.max(lower_bound),
+            window: Duration::from_secs(5),
+            grow_pressure: 1.0,
+            shrink_throughput: 1.0,
+            step: 1,
+        }
+    }
+
+    /// How often pressure and throughput are measured.
+    pub fn with_window(self, window: Duration) -> Self {
+        Self { window, ..self }
+    }
+
+    /// Average number of messages waiting in routee mailboxes at which the pool grows.
+    pub fn with_grow_pressure(self, grow_pressure: f64) -> Self {
+        Self {
+            grow_pressure,
+            ..self
+        }
+    }
+
+    /// Messages handled per routee per second below which an idle pool shrinks.
+    pub fn with_shrink_throughput(self, shrink_throughput: f64) -> Self {
+        Self {
+            shrink_throughput,
+            ..self
+        }
+    }
+
+    /// Number of routees added or removed at once.
+    pub fn with_step(self, step: usize) -> Self {
+        Self {
+            step: step.max(1),
+            ..self
+        }
+    }
+
+    #[inline]
+    pub(crate) fn clamp(&self, size: usize) -> usize {
+        size.clamp(self.lower_bound, self.upper_bound)
+    }
+
+    /// Change of the pool size given the average mailbox length and number of messages routed
+    /// during the last window.
+    pub(crate) fn resize(&self, routees: usize, pressure: f64, routed: u64) -> isize {
+        if routees < self.lower_bound {
+            return (self.lower_bound - routees) as isize;
+        }
+        if routees > self.upper_bound {
+            return -((routees - self.upper_bound) as isize);
+        }
+        let throughput = routed as f64 / self.window.as_secs_f64() / routees.max(1) as f64;
+        if pressure >= self.grow_pressure {
+            self.step.min(self.upper_bound - routees) as isize
+        } else if pressure == 0.0 && throughput < self.shrink_throughput {
+            -(self.step.min(routees - self.lower_bound) as isize)
+        } else {
+            0
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::router::Resizer;
+    use std::time::Duration;
+
+    #[test]
+    fn should_resize_within_bounds() {
+        let resizer = Resizer::new(2, 5)
+            .with_window(Duration::from_secs(1))
+            .with_step(2);
+
+        assert_eq!(2, resizer.resize(0, 0.0, 0));
+        assert_eq!(-1, resizer.resize(6, 0.0, 100));
+        assert_eq!(2, resizer.resize(2, 3.0, 100));
+        assert_eq!(1, resizer.resize(4, 1.0, 100));
+        assert_eq!(0, resizer.resize(5, 10.0, 100));
+        assert_eq!(0, resizer.resize(4, 0.5, 0));
+        assert_eq!(0, resizer.resize(4, 0.0, 8));
+        assert_eq!(-2, resizer.resize(4, 0.0, 3));
+        assert_eq!(0, resizer.resize(2, 0.0, 0));
+    }
+}
@@ -0,0 +1,115 @@
+use crate::actor::Actor;
+use crate::context::ActorContext;
+use crate::context::SenderContext;
+use crate::message::{Pid, Started, Stopping, Terminated};
+use crate::router::router_state::RouterState;
+use crate::router::{AddRoutee, GetRoutees, RemoveRoutee, Routees, RouterConfig, RouterKind};
+use async_trait::async_trait;
+use log::debug;
+use std::sync::Arc;
+
+/// Spawns or watches the routees and handles router management messages.
+pub(crate) struct RouterActor {
+    config: RouterConfig,
+    state: Arc<RouterState>,
+}
+
+impl RouterActor {
+    pub(crate) fn new(config: RouterConfig, state: Arc<RouterState>) -> Self {
+        Self { config, state }
+    }
+
+    #[inline]
+    fn is_pool(&self) -> bool {
+        matches!(self.config.kind, RouterKind::Pool { .. })
+    }
+
+    fn schedule_resize(&self, ctx: &ActorContext) {
+        if let (Some(resizer), true) = (&self.config.resizer, self.is_pool()) {
+            let window = tokio::time::sleep(resizer.window);
+            ctx.reenter_after(window, |router: &mut RouterActor, ctx, ()| {
+                router.resize(ctx);
+                router.schedule_resize(ctx);
+            });
+        }
+    }
+
+    fn resize(&mut self, ctx: &mut ActorContext) {
+        let (Some(resizer), RouterKind::Pool { props, .. }) =
+            (&self.config.resizer, &self.config.kind)
+        else {
+            return;
+        };
+        let routees = self.state.routees();
+        let waiting: usize = routees
+            .iter()
+            .filter_map(|pid| {
+                let process = ctx.get_system().registry().get(pid)?;
+                process.get_diagnostics(pid).mailbox_length
+            })
+            .sum();
+        let pressure = waiting as f64 / routees.len().max(1) as f64;
+        let change = resizer.resize(routees.len(), pressure, self.state.take_routed());
+        if change > 0 {
+            debug!("Growing {} by {} routees", ctx.get_self(), change);
+            for _ in 0..change {
+                let routee = ctx.spawn(props.clone());
+                self.state.add(routee);
+            }
+        } else if change < 0 {
+            debug!("Shrinking {} by {} routees", ctx.get_self(), -change);
+            let removed = routees.iter().rev().filter(|pid| self.is_child(ctx, pid));
+            for routee in removed.take(change.unsigned_abs()) {
+                self.state.remove(routee);
+                ctx.poison(routee);
+            }
+        }
+    }
+
+    /// Pool routees are spawned by the router with its [Pid] as parent.
+    fn is_child(&self, ctx: &ActorContext, routee: &Pid) -> bool {
+        self.is_pool() && routee.id.starts_with(&format!("{}/", ctx.get_self().id))
+    }
+}
+
+#[async_trait]
+impl Actor for RouterActor {
+    async fn receive(&mut self, ctx: &mut ActorContext) {
+        let message = ctx.get_message();
+        if message.is::<Started>() {
+            if self.state.start_resizing() {
+                self.schedule_resize(ctx);
+            }
+            if !self.is_pool() {
+                for routee in self.state.routees() {
+                    ctx.watch(&routee);
+                }
+            }
+        } else if message.is::<Stopping>() {
+            for routee in self.state.routees() {
+                if self.is_child(ctx, &routee) {
+                    ctx.stop(&routee);
+                }
+            }
+        } else if let Some(AddRoutee { pid }) = message.downcast_ref::<AddRoutee>() {
+            if self.state.add(pid.clone()) && !self.is_child(ctx, pid) {
+                ctx.watch(pid);
+            }
+        } else if let Some(RemoveRoutee { pid }) = message.downcast_ref::<RemoveRoutee>() {
+            if self.state.remove(pid) {
+                if self.is_child(ctx, pid) {
+                    ctx.poison(pid);
+                } else {
+                    ctx.unwatch(pid);
+                }
+            }
+        } else if message.is::<GetRoutees>() {
+            ctx.respond(Routees {
+                pids: self.state.routees(),
+            });
+        } else if let Some(Terminated { who: Some(who), .. }) = message.downcast_ref::<Terminated>()
+        {
+            self.state.remove(who);
+        }
+    }
+}
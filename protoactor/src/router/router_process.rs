@@ -0,0 +1,52 @@
+use crate::diagnostics::ProcessDiagnostics;
+use crate::message::{AnyMessage, MessageEnvelope, Pid, PoisonPill, SystemMessage};
+use crate::process::{ActorProcess, Process};
+use crate::router::router_state::RouterState;
+use crate::router::{AddRoutee, GetRoutees, RemoveRoutee};
+use crate::system::ActorSystem;
+use std::sync::Arc;
+
+/// Registered in place of the router actor process, routes user messages straight to the
+/// routees and passes everything else to the router actor.
+pub(crate) struct RouterProcess {
+    router: Arc<ActorProcess>,
+    state: Arc<RouterState>,
+}
+
+impl RouterProcess {
+    pub(crate) fn new(router: Arc<ActorProcess>, state: Arc<RouterState>) -> Self {
+        Self { router, state }
+    }
+
+    fn is_management(message: &AnyMessage) -> bool {
+        message.is::<AddRoutee>()
+            || message.is::<RemoveRoutee>()
+            || message.is::<GetRoutees>()
+            || message.is::<PoisonPill>()
+    }
+}
+
+impl Process for RouterProcess {
+    #[inline]
+    fn system(&self) -> Arc<ActorSystem> {
+        self.router.system()
+    }
+
+    fn send_user_message(&self, pid: &Pid, envelope: MessageEnvelope<AnyMessage>) {
+        if Self::is_management(envelope.get_message()) {
+            self.router.send_user_message(pid, envelope)
+        } else {
+            self.state.route(&self.system(), pid, envelope)
+        }
+    }
+
+    #[inline]
+    fn send_system_message(&self, pid: &Pid, msg: SystemMessage) {
+        self.router.send_system_message(pid, msg)
+    }
+
+    #[inline]
+    fn get_diagnostics(&self, pid: &Pid) -> ProcessDiagnostics {
+        self.router.get_diagnostics(pid)
+    }
+}
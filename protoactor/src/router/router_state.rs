@@ -0,0 +1,140 @@
+use crate::message::{AnyMessage, MessageEnvelope, Pid};
+use crate::router::{RouterConfig, RoutingStrategy};
+use crate::system::ActorSystem;
+use rand::Rng;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+
+/// Points each routee occupies on the consistent hash ring.
+const REPLICAS: usize = 100;
+
+#[derive(Default)]
+struct Routees {
+    pids: Vec<Pid>,
+    /// Sorted hash ring of `(hash, index into pids)`, kept only for consistent hash routing.
+    ring: Vec<(u32, usize)>,
+}
+
+/// Routees and routing position shared by the router process and the router actor.
+pub(crate) struct RouterState {
+    strategy: RoutingStrategy,
+    routees: RwLock<Routees>,
+    next: AtomicUsize,
+    /// Messages routed since the resizer last looked.
+    routed: AtomicU64,
+    resizing: AtomicBool,
+}
+
+impl RouterState {
+    pub(crate) fn new(config: &RouterConfig) -> Arc<Self> {
+        Arc::new(Self {
+            strategy: config.strategy.clone(),
+            routees: Default::default(),
+            next: AtomicUsize::new(0),
+            routed: AtomicU64::new(0),
+            resizing: AtomicBool::new(false),
+        })
+    }
+
+    /// Number of messages routed since the previous call.
+    pub(crate) fn take_routed(&self) -> u64 {
+        self.routed.swap(0, Ordering::Relaxed)
+    }
+
+    /// Returns `true` only for the first call, so the router starts resizing once.
+    pub(crate) fn start_resizing(&self) -> bool {
+        !self.resizing.swap(true, Ordering::Relaxed)
+    }
+
+    pub(crate) fn routees(&self) -> Vec<Pid> {
+        self.routees.read().unwrap().pids.clone()
+    }
+
+    /// Returns `false` if the process already is a routee.
+    pub(crate) fn add(&self, pid: Pid) -> bool {
+        let mut routees = self.routees.write().unwrap();
+        if routees.pids.contains(&pid) {
+            return false;
+        }
+        routees.pids.push(pid);
+        self.rebuild_ring(&mut routees);
+        true
+    }
+
+    /// Returns `false` if the process is not a routee.
+    pub(crate) fn remove(&self, pid: &Pid) -> bool {
+        let mut routees = self.routees.write().unwrap();
+        let length = routees.pids.len();
+        routees.pids.retain(|routee| routee != pid);
+        if routees.pids.len() == length {
+            return false;
+        }
+        self.rebuild_ring(&mut routees);
+        true
+    }
+
+    /// Sends the message to routees chosen by the strategy, or to dead letters if there are
+    /// none.
+    pub(crate) fn route(
+        &self,
+        system: &ActorSystem,
+        pid: &Pid,
+        envelope: MessageEnvelope<AnyMessage>,
+    ) {
+        self.routed.fetch_add(1, Ordering::Relaxed);
+        let routees = self.routees.read().unwrap();
+        let selected = match (&self.strategy, routees.pids.len()) {
+            (_, 0) => None,
+            (RoutingStrategy::RoundRobin, length) => {
+                Some(self.next.fetch_add(1, Ordering::Relaxed) % length)
+            }
+            (RoutingStrategy::Random, length) => Some(rand::thread_rng().gen_range(0..length)),
+            (RoutingStrategy::Broadcast, _) => {
+                for routee in &routees.pids {
+                    system
+                        .get_process(routee)
+                        .send_user_message(routee, envelope.clone());
+                }
+                return;
+            }
+            (RoutingStrategy::ConsistentHash(key), _) => key
+                .get(&envelope)
+                .map(|key| Self::find_on_ring(&routees.ring, hash(key.as_bytes()))),
+        };
+        match selected {
+            Some(index) => {
+                let routee = &routees.pids[index];
+                system
+                    .get_process(routee)
+                    .send_user_message(routee, envelope)
+            }
+            None => system.dead_letter().send_user_message(pid, envelope),
+        }
+    }
+
+    fn rebuild_ring(&self, routees: &mut Routees) {
+        if !matches!(self.strategy, RoutingStrategy::ConsistentHash(_)) {
+            return;
+        }
+        let mut ring = Vec::with_capacity(routees.pids.len() * REPLICAS);
+        for (index, pid) in routees.pids.iter().enumerate() {
+            for replica in 0..REPLICAS {
+                ring.push((hash(format!("{}#{}", pid, replica).as_bytes()), index));
+            }
+        }
+        ring.sort_unstable();
+        routees.ring = ring;
+    }
+
+    fn find_on_ring(ring: &[(u32, usize)], hash: u32) -> usize {
+        let position = ring.partition_point(|(point, _)| *point < hash);
+        ring[position % ring.len()].1
+    }
+}
+
+/// 32-bit FNV-1a, stable across processes and platforms.
+fn hash(bytes: &[u8]) -> u32 {
+    bytes.iter().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ *byte as u32).wrapping_mul(0x01000193)
+    })
+}
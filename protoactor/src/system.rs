@@ -1,16 +1,151 @@
-use crate::process::Registry;
+use crate::context::RootContext;
+use crate::diagnostics::{ProcessDiagnostics, SystemDiagnostics};
+use crate::message::{Pid, SystemMessage};
+use crate::metrics::{ActorMetrics, InMemoryMetricsRecorder};
+use crate::process::{DeadLetterProcess, Process, Registry};
 use config::ActorSystemConfig;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::oneshot;
 
 pub mod config;
+mod event_stream;
 
-const NO_HOST: &str = "nohost";
+pub use event_stream::*;
+
+/// Address of actor systems that are not reachable over network.
+pub(crate) const NO_HOST: &str = "nohost";
+#[allow(dead_code)]
 const CLIENT: &str = "$client";
 
 pub struct ActorSystem {
-    host: String,
-    port: i16,
     config: ActorSystemConfig,
     registry: Registry,
+    dead_letter: Arc<DeadLetterProcess>,
+    event_stream: EventStream,
+    metrics: Option<ActorMetrics>,
 }
 
-impl ActorSystem {}
+impl ActorSystem {
+    /// Creates local actor system with default configuration.
+    pub fn new() -> Arc<Self> {
+        Self::with_config(ActorSystemConfig::default())
+    }
+
+    /// Creates local actor system.
+    pub fn with_config(config: ActorSystemConfig) -> Arc<Self> {
+        let metrics = config.metrics_enabled.then(|| {
+            let recorder = config
+                .metrics_recorder
+                .clone()
+                .unwrap_or_else(|| Arc::new(InMemoryMetricsRecorder::new()));
+            ActorMetrics::new(NO_HOST.to_string(), recorder)
+        });
+        Arc::new_cyclic(|system| Self {
+            config,
+            registry: Registry::new(NO_HOST.to_string()),
+            dead_letter: Arc::new(DeadLetterProcess::new(system.clone())),
+            event_stream: EventStream::default(),
+            metrics,
+        })
+    }
+
+    /// Address of this actor system, [Pid]s of all local processes share it. It is `nohost`
+    /// if the actor system is not reachable over network.
+    pub fn address(&self) -> String {
+        self.registry.address()
+    }
+
+    /// Makes the actor system reachable on the address, [Pid]s of processes spawned afterwards
+    /// carry it.
+    #[cfg_attr(not(feature = "remote"), allow(dead_code))]
+    pub(crate) fn set_address(&self, address: String) {
+        if let Some(metrics) = &self.metrics {
+            metrics.set_address(address.clone());
+        }
+        self.registry.set_address(address);
+    }
+
+    /// Context used to spawn and communicate with actors from outside of the actor system.
+    pub fn root(self: &Arc<Self>) -> RootContext {
+        RootContext::new(self.clone())
+    }
+
+    #[inline]
+    pub fn config(&self) -> &ActorSystemConfig {
+        &self.config
+    }
+
+    /// Actor metrics, available only if they are enabled in [ActorSystemConfig].
+    #[inline]
+    pub fn metrics(&self) -> Option<&ActorMetrics> {
+        self.metrics.as_ref()
+    }
+
+    #[inline]
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    #[inline]
+    pub fn event_stream(&self) -> &EventStream {
+        &self.event_stream
+    }
+
+    /// Process that receives messages sent to processes that do not exist.
+    pub fn dead_letter(&self) -> Arc<dyn Process> {
+        self.dead_letter.clone()
+    }
+
+    /// Finds process of the [Pid], or [DeadLetterProcess] if process does not exist.
+    pub fn get_process(&self, pid: &Pid) -> Arc<dyn Process> {
+        self.registry.get(pid).unwrap_or_else(|| self.dead_letter())
+    }
+
+    /// Diagnostics of a local process, including the children of actors and the state of those
+    /// implementing [Diagnostics](crate::diagnostics::Diagnostics). Waits for the actor to
+    /// finish the message it is processing, up to the actor request timeout, and leaves the
+    /// state and children empty after that.
+    pub async fn diagnostics(&self, pid: &Pid) -> Option<ProcessDiagnostics> {
+        if !self.registry.is_local(pid) {
+            return None;
+        }
+        let process = self.registry.get(pid)?;
+        let mut diagnostics = process.get_diagnostics(pid);
+        let (sender, receiver) = oneshot::channel();
+        process.send_system_message(pid, SystemMessage::Diagnostics(sender));
+        let reply = tokio::time::timeout(self.config.actor_request_timeout, receiver).await;
+        if let Some(reply) = reply.ok().and_then(Result::ok) {
+            diagnostics.state = reply.state;
+            diagnostics.children = reply.children;
+        }
+        Some(diagnostics)
+    }
+
+    /// Diagnostics of every registered local process, without actor states.
+    pub fn diagnostics_snapshot(&self) -> SystemDiagnostics {
+        let mut processes = self
+            .registry
+            .processes()
+            .into_iter()
+            .map(|(pid, process)| process.get_diagnostics(&pid))
+            .collect::<Vec<_>>();
+        processes.sort_by(|left, right| left.pid.id.cmp(&right.pid.id));
+        let mut children = HashMap::<Pid, Vec<Pid>>::new();
+        for process in &processes {
+            if let Some(parent) = &process.parent {
+                children
+                    .entry(parent.clone())
+                    .or_default()
+                    .push(process.pid.clone());
+            }
+        }
+        for process in &mut processes {
+            process.children = children.remove(&process.pid).unwrap_or_default();
+        }
+        SystemDiagnostics {
+            address: self.address(),
+            processes,
+        }
+    }
+}
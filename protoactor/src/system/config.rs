@@ -1,16 +1,23 @@
+use crate::metrics::MetricsRecorder;
+use std::sync::Arc;
 use std::time::Duration;
 
 #[derive(Debug)]
 pub struct ActorSystemConfig {
-    pub(super) dead_letter_throttle_interval: Duration,
-    pub(super) dead_letter_throttle_count: i32,
-    pub(super) dead_letter_request_logging: bool,
-    pub(super) dead_letter_response_logging: bool,
-    pub(super) developer_supervision_logging: bool,
-    pub(super) metrics_enabled: bool,
-    pub(super) shared_futures: bool,
-    pub(super) shared_futures_size: usize,
-    pub(super) actor_request_timeout: Duration,
+    pub(crate) dead_letter_throttle_interval: Duration,
+    pub(crate) dead_letter_throttle_count: i32,
+    pub(crate) dead_letter_request_logging: bool,
+    pub(crate) dead_letter_response_logging: bool,
+    #[allow(dead_code)]
+    pub(crate) developer_supervision_logging: bool,
+    pub(crate) metrics_enabled: bool,
+    pub(crate) metrics_recorder: Option<Arc<dyn MetricsRecorder>>,
+    #[allow(dead_code)]
+    pub(crate) shared_futures: bool,
+    #[allow(dead_code)]
+    pub(crate) shared_futures_size: usize,
+    pub(crate) actor_request_timeout: Duration,
+    pub(crate) propagated_headers: Vec<String>,
 }
 
 impl Default for ActorSystemConfig {
@@ -22,9 +29,11 @@ impl Default for ActorSystemConfig {
             dead_letter_response_logging: true,
             developer_supervision_logging: true,
             metrics_enabled: false,
+            metrics_recorder: None,
             shared_futures: true,
             shared_futures_size: 5000,
             actor_request_timeout: Duration::from_secs(5),
+            propagated_headers: Vec::new(),
         }
     }
 }
@@ -95,6 +104,16 @@ impl ActorSystemConfig {
         }
     }
 
+    /// Enables actor metrics and reports them to the given recorder instead of the default
+    /// [InMemoryMetricsRecorder](crate::metrics::InMemoryMetricsRecorder).
+    pub fn with_metrics_recorder(self, recorder: Arc<dyn MetricsRecorder>) -> Self {
+        Self {
+            metrics_enabled: true,
+            metrics_recorder: Some(recorder),
+            ..self
+        }
+    }
+
     pub fn with_actor_request_timeout(self, timeout: Duration) -> Self {
         Self {
             actor_request_timeout: timeout,
@@ -109,4 +128,19 @@ impl ActorSystemConfig {
             ..self
         }
     }
+
+    /// Header keys that are copied from the message an actor is processing into every message
+    /// the actor sends, requests or responds with while processing it.
+    ///
+    /// Header that is explicitly set on the outgoing message is not overwritten.
+    pub fn with_propagated_headers<I, K>(self, keys: I) -> Self
+    where
+        I: IntoIterator<Item = K>,
+        K: Into<String>,
+    {
+        Self {
+            propagated_headers: keys.into_iter().map(Into::into).collect(),
+            ..self
+        }
+    }
 }
@@ -0,0 +1,106 @@
+use crate::message::{AnyMessage, Message};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+
+type Handler = dyn Fn(&AnyMessage) + Send + Sync;
+
+/// Publishes events of the actor system to everyone subscribed, such as
+/// `MessageTooLarge` of remoting.
+#[derive(Default)]
+pub struct EventStream {
+    next_id: AtomicU64,
+    subscriptions: RwLock<Vec<(u64, Arc<Handler>)>>,
+}
+
+/// Subscription to the [EventStream], ends with [EventStream::unsubscribe].
+#[derive(Debug, PartialEq, Eq)]
+pub struct Subscription(u64);
+
+impl EventStream {
+    /// Calls the handler with every event published until unsubscribed. Handlers run on the
+    /// publishing thread and should not block, e.g. forward events to a channel or an actor.
+    pub fn subscribe<F>(&self, handler: F) -> Subscription
+    where
+        F: Fn(&AnyMessage) + Send + Sync + 'static,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        self.subscriptions
+            .write()
+            .unwrap()
+            .push((id, Arc::new(handler)));
+        Subscription(id)
+    }
+
+    /// Subscribes to events of type `E` only.
+    pub fn subscribe_to<E, F>(&self, handler: F) -> Subscription
+    where
+        E: 'static,
+        F: Fn(&E) + Send + Sync + 'static,
+    {
+        self.subscribe(move |event| {
+            if let Some(event) = event.downcast_ref::<E>() {
+                handler(event);
+            }
+        })
+    }
+
+    pub fn unsubscribe(&self, subscription: Subscription) {
+        self.subscriptions
+            .write()
+            .unwrap()
+            .retain(|(id, _)| *id != subscription.0);
+    }
+
+    pub fn publish<E>(&self, event: E)
+    where
+        E: Message + Send + Sync + 'static,
+    {
+        let event = AnyMessage::new(event);
+        let handlers = self
+            .subscriptions
+            .read()
+            .unwrap()
+            .iter()
+            .map(|(_, handler)| handler.clone())
+            .collect::<Vec<_>>();
+        for handler in handlers {
+            handler(&event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::message::Message;
+    use crate::system::EventStream;
+    use std::sync::{Arc, Mutex};
+
+    struct Joined(&'static str);
+
+    impl Message for Joined {
+        type Result = ();
+    }
+
+    struct Left;
+
+    impl Message for Left {
+        type Result = ();
+    }
+
+    #[test]
+    fn should_deliver_events_until_unsubscribed() {
+        let event_stream = EventStream::default();
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let events = received.clone();
+        let subscription = event_stream.subscribe_to(move |event: &Joined| {
+            events.lock().unwrap().push(event.0);
+        });
+
+        event_stream.publish(Joined("a"));
+        event_stream.publish(Left);
+        event_stream.unsubscribe(subscription);
+        event_stream.publish(Joined("b"));
+
+        assert_eq!(vec!["a"], *received.lock().unwrap());
+    }
+}
@@ -0,0 +1,418 @@
+//! Distributed tracing of actor messages.
+//!
+//! With the `tracing` feature every actor receives user messages inside a `tracing` span and
+//! the trace is propagated between actors in the W3C `traceparent` entry of [MessageHeader].
+//! Messages sent while an actor receives, or from continuations scheduled with
+//! [ActorContext::reenter_after], are children of the receive span, so traces cross actor
+//! boundaries and, since headers travel with the envelope, process boundaries as well.
+//! Messages sent from the [RootContext](crate::context::RootContext) continue
+//! [TraceContext::current] when there is one.
+//!
+//! With the `opentelemetry` feature, receive spans are parented to the remote span context
+//! through [tracing_opentelemetry], and outgoing messages carry span ids assigned by the
+//! OpenTelemetry layer when one is installed.
+use crate::context::ActorContext;
+use crate::message::{AnyMessage, MessageEnvelope, MessageHeader};
+use rand::Rng;
+use std::fmt::{Display, Formatter};
+use std::future::Future;
+use tracing::Instrument;
+
+/// Header that carries the trace context, see <https://www.w3.org/TR/trace-context/>.
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+const SAMPLED_FLAG: u8 = 0x01;
+
+tokio::task_local! {
+    static CURRENT: TraceContext;
+}
+
+/// Position of a span in a distributed trace, as carried by the `traceparent` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceContext {
+    pub trace_id: u128,
+    pub span_id: u64,
+    pub flags: u8,
+}
+
+impl TraceContext {
+    /// Starts a new sampled trace.
+    pub fn new_root() -> Self {
+        let trace_id = ((random_u64() as u128) << 64) | random_u64() as u128;
+        Self {
+            trace_id,
+            span_id: random_u64(),
+            flags: SAMPLED_FLAG,
+        }
+    }
+
+    /// New span in the same trace.
+    pub fn child(&self) -> Self {
+        Self {
+            span_id: random_u64(),
+            ..*self
+        }
+    }
+
+    #[inline]
+    pub fn is_sampled(&self) -> bool {
+        self.flags & SAMPLED_FLAG != 0
+    }
+
+    /// Parses `traceparent` value, `None` if it is malformed or uses all-zero ids.
+    pub fn parse(traceparent: &str) -> Option<Self> {
+        let mut parts = traceparent.trim().split('-');
+        let version = parts.next().filter(|part| part.len() == 2)?;
+        let trace_id = parts.next().filter(|part| part.len() == 32)?;
+        let span_id = parts.next().filter(|part| part.len() == 16)?;
+        let flags = parts.next().filter(|part| part.len() == 2)?;
+        if version == "ff" || (version == "00" && parts.next().is_some()) {
+            return None;
+        }
+        let context = Self {
+            trace_id: u128::from_str_radix(trace_id, 16).ok()?,
+            span_id: u64::from_str_radix(span_id, 16).ok()?,
+            flags: u8::from_str_radix(flags, 16).ok()?,
+        };
+        (context.trace_id != 0 && context.span_id != 0).then_some(context)
+    }
+
+    /// Trace context carried by the headers.
+    pub fn from_header(header: &MessageHeader) -> Option<Self> {
+        header
+            .get(TRACEPARENT_HEADER)
+            .and_then(|traceparent| Self::parse(traceparent))
+    }
+
+    /// Writes the trace context into the headers, replacing one that was already there.
+    pub fn inject(&self, header: &mut MessageHeader) {
+        header.insert(TRACEPARENT_HEADER.to_string(), self.to_string());
+    }
+
+    pub(crate) fn inject_into(&self, envelope: &mut MessageEnvelope<AnyMessage>) {
+        envelope.merge_header([(TRACEPARENT_HEADER.to_string(), self.to_string())].into());
+    }
+
+    /// Trace context of the message being received by the current actor, if it is traced.
+    pub fn current() -> Option<Self> {
+        CURRENT
+            .try_with(|context| *context)
+            .ok()
+            .or_else(otel::current)
+    }
+
+    /// Runs the future with this trace context as [TraceContext::current], so messages sent
+    /// by it from the root context continue the trace.
+    pub async fn scope<F>(self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        CURRENT.scope(self, future).await
+    }
+}
+
+impl Display for TraceContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "00-{:032x}-{:016x}-{:02x}",
+            self.trace_id, self.span_id, self.flags
+        )
+    }
+}
+
+/// Receive span of a user message, kept by the [ActorContext] while the actor processes the
+/// message and by continuations the actor schedules meanwhile.
+#[derive(Clone)]
+pub(crate) struct ReceiveTrace {
+    pub(crate) context: TraceContext,
+    span: tracing::Span,
+}
+
+impl ReceiveTrace {
+    /// Starts a span in the trace carried by the envelope, or in a new trace.
+    pub(crate) fn start(ctx: &ActorContext, envelope: &MessageEnvelope<AnyMessage>) -> Self {
+        let parent = TraceContext::from_header(envelope.get_header());
+        let mut context = parent
+            .map(|parent| parent.child())
+            .unwrap_or_else(TraceContext::new_root);
+        let span = tracing::info_span!(
+            "receive",
+            actor.pid = %ctx.get_self(),
+            actor.type = ctx.get_props().get_actor_type(),
+            message.type = envelope.get_message().type_name(),
+            trace_id = %format_args!("{:032x}", context.trace_id),
+            span_id = tracing::field::Empty,
+            parent_span_id = tracing::field::Empty,
+        );
+        if let Some(parent) = parent {
+            span.record("parent_span_id", format_args!("{:016x}", parent.span_id));
+        }
+        otel::link(&span, parent, &mut context);
+        span.record("span_id", format_args!("{:016x}", context.span_id));
+        Self { context, span }
+    }
+
+    /// Runs the future in the span, with the trace as [TraceContext::current].
+    pub(crate) async fn instrument<F>(self, future: F) -> F::Output
+    where
+        F: Future,
+    {
+        let Self { context, span } = self;
+        context.scope(future.instrument(span)).await
+    }
+
+    /// Runs `f` in the span, with the trace as [TraceContext::current].
+    pub(crate) fn in_scope<R>(&self, f: impl FnOnce() -> R) -> R {
+        self.span.in_scope(|| CURRENT.sync_scope(self.context, f))
+    }
+}
+
+/// Random id, never zero as W3C Trace Context forbids all-zero ids.
+fn random_u64() -> u64 {
+    rand::thread_rng().gen_range(1..=u64::MAX)
+}
+
+#[cfg(feature = "opentelemetry")]
+mod otel {
+    use super::TraceContext;
+    use opentelemetry::trace::{
+        SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState,
+    };
+    use tracing_opentelemetry::OpenTelemetrySpanExt;
+
+    /// Parents the span to the remote span and adopts ids assigned by the OpenTelemetry layer.
+    pub(super) fn link(
+        span: &tracing::Span,
+        parent: Option<TraceContext>,
+        context: &mut TraceContext,
+    ) {
+        if let Some(parent) = parent {
+            let remote = SpanContext::new(
+                TraceId::from_bytes(parent.trace_id.to_be_bytes()),
+                SpanId::from_bytes(parent.span_id.to_be_bytes()),
+                TraceFlags::new(parent.flags),
+                true,
+                TraceState::default(),
+            );
+            span.set_parent(opentelemetry::Context::new().with_remote_span_context(remote));
+        }
+        if let Some(assigned) = from_span(span) {
+            *context = assigned;
+        }
+    }
+
+    /// Trace context of the current `tracing` span, if OpenTelemetry layer tracks it.
+    pub(super) fn current() -> Option<TraceContext> {
+        from_span(&tracing::Span::current())
+    }
+
+    fn from_span(span: &tracing::Span) -> Option<TraceContext> {
+        let context = span.context();
+        let span_context = context.span().span_context().clone();
+        span_context.is_valid().then(|| TraceContext {
+            trace_id: u128::from_be_bytes(span_context.trace_id().to_bytes()),
+            span_id: u64::from_be_bytes(span_context.span_id().to_bytes()),
+            flags: span_context.trace_flags().to_u8(),
+        })
+    }
+}
+
+#[cfg(not(feature = "opentelemetry"))]
+mod otel {
+    use super::TraceContext;
+
+    #[inline]
+    pub(super) fn link(_: &tracing::Span, _: Option<TraceContext>, _: &mut TraceContext) {}
+
+    #[inline]
+    pub(super) fn current() -> Option<TraceContext> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actor::{Actor, Props};
+    use crate::context::{ActorContext, SenderContext};
+    use crate::message::{AnyMessage, Message, MessageEnvelope, MessageHeader, Pid};
+    use crate::system::ActorSystem;
+    use crate::trace::TraceContext;
+    use async_trait::async_trait;
+
+    struct Forward(Pid);
+
+    struct Inspect;
+
+    struct Traces {
+        forwarded: Option<TraceContext>,
+        received: Option<TraceContext>,
+        current: Option<TraceContext>,
+    }
+
+    impl Message for Forward {
+        type Result = Traces;
+    }
+
+    impl Message for Inspect {
+        type Result = Traces;
+    }
+
+    impl Message for Traces {
+        type Result = ();
+    }
+
+    struct ForwardActor;
+
+    #[async_trait]
+    impl Actor for ForwardActor {
+        async fn receive(&mut self, ctx: &mut ActorContext) {
+            if let Some(Forward(target)) = ctx.get_message().downcast_ref::<Forward>() {
+                let current = TraceContext::current();
+                let traces = ctx.request_future(target, Inspect).await.unwrap();
+                ctx.respond(Traces {
+                    forwarded: current,
+                    ..traces
+                });
+            }
+        }
+    }
+
+    /// Forwards from a continuation scheduled by another continuation.
+    struct ReenterActor;
+
+    #[async_trait]
+    impl Actor for ReenterActor {
+        async fn receive(&mut self, ctx: &mut ActorContext) {
+            if let Some(Forward(target)) = ctx.get_message().downcast_ref::<Forward>() {
+                let response = ctx.request_future(target, Inspect);
+                ctx.reenter_after(response, |_: &mut ReenterActor, ctx, _| {
+                    let forward = ctx.get_message();
+                    let Forward(target) = forward.downcast_ref::<Forward>().unwrap();
+                    let response = ctx.request_future(target, Inspect);
+                    ctx.reenter_after(response, |_: &mut ReenterActor, ctx, traces| {
+                        ctx.respond(traces.unwrap())
+                    });
+                });
+            }
+        }
+    }
+
+    /// Forwards with a traceparent of its own.
+    struct ExplicitActor(TraceContext);
+
+    #[async_trait]
+    impl Actor for ExplicitActor {
+        async fn receive(&mut self, ctx: &mut ActorContext) {
+            if let Some(Forward(target)) = ctx.get_message().downcast_ref::<Forward>() {
+                let mut header = MessageHeader::new();
+                self.0.inject(&mut header);
+                let inspect = AnyMessage::new(Inspect);
+                let sender = ctx.get_sender().cloned();
+                ctx.send_envelope(target, MessageEnvelope::new(inspect, sender, Some(header)));
+            }
+        }
+    }
+
+    struct InspectActor;
+
+    #[async_trait]
+    impl Actor for InspectActor {
+        async fn receive(&mut self, ctx: &mut ActorContext) {
+            if ctx.get_message().is::<Inspect>() {
+                ctx.respond(Traces {
+                    forwarded: None,
+                    received: TraceContext::from_header(ctx.get_envelope().get_header()),
+                    current: TraceContext::current(),
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn should_parse_and_format_traceparent() {
+        let traceparent = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+        let context = TraceContext::parse(traceparent).unwrap();
+
+        assert_eq!(0x4bf92f3577b34da6a3ce929d0e0e4736, context.trace_id);
+        assert_eq!(0x00f067aa0ba902b7, context.span_id);
+        assert!(context.is_sampled());
+        assert_eq!(traceparent, context.to_string());
+        assert_eq!(
+            None,
+            TraceContext::parse("00-0af7651916cd43dd-00f067aa0ba902b7-01")
+        );
+        assert_eq!(
+            None,
+            TraceContext::parse("00-00000000000000000000000000000000-00f067aa0ba902b7-01")
+        );
+        assert_eq!(
+            None,
+            TraceContext::parse("ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01")
+        );
+    }
+
+    #[tokio::test]
+    async fn should_propagate_trace_across_actors() {
+        let system = ActorSystem::new();
+        let root = system.root();
+        let inspect = root.spawn(Props::from_producer(|| InspectActor));
+        let forward = root.spawn(Props::from_producer(|| ForwardActor));
+        let origin = TraceContext::new_root();
+        let mut header = MessageHeader::new();
+        origin.inject(&mut header);
+
+        let traces = root
+            .with_headers(header)
+            .request_future(&forward, Forward(inspect))
+            .await
+            .unwrap();
+
+        let forwarded = traces.forwarded.unwrap();
+        let received = traces.received.unwrap();
+        let current = traces.current.unwrap();
+        assert_eq!(origin.trace_id, forwarded.trace_id);
+        assert_ne!(origin.span_id, forwarded.span_id);
+        assert_eq!(forwarded, received);
+        assert_eq!(origin.trace_id, current.trace_id);
+        assert_ne!(received.span_id, current.span_id);
+    }
+
+    #[tokio::test]
+    async fn should_continue_trace_in_continuations() {
+        let system = ActorSystem::new();
+        let root = system.root();
+        let inspect = root.spawn(Props::from_producer(|| InspectActor));
+        let reenter = root.spawn(Props::from_producer(|| ReenterActor));
+        let origin = TraceContext::new_root();
+
+        let traces = origin
+            .scope(async { root.request_future(&reenter, Forward(inspect)).await })
+            .await
+            .unwrap();
+
+        let received = traces.received.unwrap();
+        assert_eq!(origin.trace_id, received.trace_id);
+        assert_ne!(origin.span_id, received.span_id);
+    }
+
+    #[tokio::test]
+    async fn should_keep_explicit_traceparent() {
+        let system = ActorSystem::new();
+        let root = system.root();
+        let inspect = root.spawn(Props::from_producer(|| InspectActor));
+        let explicit = TraceContext::new_root();
+        let forward = root.spawn(Props::from_producer(move || ExplicitActor(explicit)));
+        let mut header = MessageHeader::new();
+        TraceContext::new_root().inject(&mut header);
+
+        let traces = root
+            .with_headers(header)
+            .request_future(&forward, Forward(inspect))
+            .await
+            .unwrap();
+
+        assert_eq!(Some(explicit), traces.received);
+    }
+}